@@ -0,0 +1,316 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use ::zip::{read::ZipFile, ExtraField, ZipArchive};
+use flate2::read::MultiGzDecoder;
+use serde::{Deserialize, Serialize};
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZReader};
+use xz2::read::XzDecoder;
+
+// Signatures en tête de fichier
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_EMPTY_MAGIC: &[u8] = b"PK\x05\x06";
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const XZ_MAGIC: &[u8] = &[0xFD, b'7', b'z', b'X', b'Z', 0x00];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+const SEVEN_Z_MAGIC: &[u8] = &[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+// Un tar non compressé n'a pas de signature en tête : "ustar" suit le premier en-tête
+const TAR_MAGIC_OFFSET: usize = 257;
+const TAR_MAGIC: &[u8] = b"ustar";
+
+// Attribut 7-Zip signalant un mode Unix dans les 16 bits de poids fort des attributs Windows
+const SEVEN_Z_UNIX_EXTENSION: u32 = 0x8000;
+const UNIX_FILE_TYPE_MASK: u32 = 0o170000;
+const UNIX_SYMLINK: u32 = 0o120000;
+
+/// Format d'une archive de jeu, reconnu à ses premiers octets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+    SevenZ,
+}
+
+impl ArchiveFormat {
+    pub fn detect(path: &Path) -> Result<Self, String> {
+        let mut header = Vec::new();
+        File::open(path)
+            .and_then(|file| file.take((TAR_MAGIC_OFFSET + TAR_MAGIC.len()) as u64).read_to_end(&mut header))
+            .map_err(|e| format!("Failed to open archive: {}", e))?;
+
+        let format = if header.starts_with(ZIP_MAGIC) || header.starts_with(ZIP_EMPTY_MAGIC) {
+            ArchiveFormat::Zip
+        } else if header.starts_with(SEVEN_Z_MAGIC) {
+            ArchiveFormat::SevenZ
+        } else if header.starts_with(GZIP_MAGIC) {
+            ArchiveFormat::TarGz
+        } else if header.starts_with(XZ_MAGIC) {
+            ArchiveFormat::TarXz
+        } else if header.starts_with(ZSTD_MAGIC) {
+            ArchiveFormat::TarZst
+        } else if header.get(TAR_MAGIC_OFFSET..).is_some_and(|magic| magic.starts_with(TAR_MAGIC)) {
+            ArchiveFormat::Tar
+        } else {
+            return Err(format!("Unsupported archive format: {}", path.display()));
+        };
+        Ok(format)
+    }
+}
+
+/// Nature d'une entrée
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+    // Lien physique, périphérique, tube nommé...
+    Unsupported,
+}
+
+/// Description d'une entrée, commune à tous les formats
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub kind: EntryKind,
+    // Taille décompressée annoncée
+    pub size: u64,
+    // Inconnue pour les formats compressés d'un seul bloc (tar compressé, 7z solide)
+    pub compressed_size: Option<u64>,
+    pub mode: Option<u32>,
+    pub modified: Option<SystemTime>,
+    // Cible d'un lien quand le format la range dans l'en-tête ; sinon c'est le contenu de l'entrée
+    pub link_target: Option<String>,
+}
+
+/// Archive dont le format a été reconnu
+pub struct Archive {
+    path: PathBuf,
+    format: ArchiveFormat,
+    file_size: u64,
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let format = ArchiveFormat::detect(path)?;
+        let file_size = std::fs::metadata(path)
+            .map_err(|e| format!("Failed to open archive: {}", e))?
+            .len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            file_size,
+        })
+    }
+
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    // Taille du fichier d'archive, pour le taux de compression des formats sans taille par entrée
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Parcourir les en-têtes sans rien extraire. Un tar compressé doit être décompressé
+    /// pour atteindre chaque en-tête : ce parcours coûte alors une lecture complète.
+    pub fn scan<E: From<String>>(&self, mut each: impl FnMut(&ArchiveEntry) -> Result<(), E>) -> Result<(), E> {
+        match self.format {
+            ArchiveFormat::Zip => {
+                let mut archive = self.open_zip()?;
+                for i in 0..archive.len() {
+                    let file = archive
+                        .by_index_raw(i)
+                        .map_err(|e| format!("Cannot access entry: {}", e))?;
+                    each(&zip_entry(&file))?;
+                }
+                Ok(())
+            }
+            ArchiveFormat::SevenZ => {
+                let reader = self.open_seven_z()?;
+                for entry in reader.archive().files.iter().filter(|entry| !entry.is_anti_item()) {
+                    each(&seven_z_entry(entry))?;
+                }
+                Ok(())
+            }
+            _ => self.for_each_tar_entry(|entry, _| each(entry)),
+        }
+    }
+
+    /// Lire chaque entrée avec son contenu. L'ordre est celui de l'archive, sauf pour le 7z
+    /// où les entrées vides (dossiers compris) viennent en dernier.
+    pub fn extract<E: From<String>>(
+        &self,
+        mut each: impl FnMut(&ArchiveEntry, &mut dyn Read) -> Result<(), E>,
+    ) -> Result<(), E> {
+        match self.format {
+            ArchiveFormat::Zip => {
+                let mut archive = self.open_zip()?;
+                for i in 0..archive.len() {
+                    let mut file = archive
+                        .by_index(i)
+                        .map_err(|e| format!("Cannot access entry: {}", e))?;
+                    let entry = zip_entry(&file);
+                    each(&entry, &mut file)?;
+                }
+                Ok(())
+            }
+            ArchiveFormat::SevenZ => {
+                let mut reader = self.open_seven_z()?;
+                let mut failure = None;
+                reader
+                    .for_each_entries(|entry, content| {
+                        if entry.is_anti_item() {
+                            return Ok(true);
+                        }
+                        if let Err(e) = each(&seven_z_entry(entry), content) {
+                            failure = Some(e);
+                            return Ok(false);
+                        }
+                        // Dans un bloc solide, l'entrée suivante commence où celle-ci se termine
+                        io::copy(content, &mut io::sink())?;
+                        Ok(true)
+                    })
+                    .map_err(|e| format!("Invalid 7z archive: {}", e))?;
+                failure.map_or(Ok(()), Err)
+            }
+            _ => self.for_each_tar_entry(each),
+        }
+    }
+
+    fn open_zip(&self) -> Result<ZipArchive<File>, String> {
+        let file = File::open(&self.path).map_err(|e| format!("Failed to open zip: {}", e))?;
+        ZipArchive::new(file).map_err(|e| format!("Invalid zip archive: {}", e))
+    }
+
+    fn open_seven_z(&self) -> Result<SevenZReader<File>, String> {
+        SevenZReader::open(&self.path, Password::empty()).map_err(|e| format!("Invalid 7z archive: {}", e))
+    }
+
+    fn tar_stream(&self) -> Result<Box<dyn Read>, String> {
+        let file = BufReader::new(File::open(&self.path).map_err(|e| format!("Failed to open archive: {}", e))?);
+        Ok(match self.format {
+            ArchiveFormat::TarGz => Box::new(MultiGzDecoder::new(file)),
+            ArchiveFormat::TarXz => Box::new(XzDecoder::new_multi_decoder(file)),
+            ArchiveFormat::TarZst => {
+                Box::new(zstd::Decoder::with_buffer(file).map_err(|e| format!("Invalid zstd stream: {}", e))?)
+            }
+            _ => Box::new(file),
+        })
+    }
+
+    fn for_each_tar_entry<E: From<String>>(
+        &self,
+        mut each: impl FnMut(&ArchiveEntry, &mut dyn Read) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut archive = tar::Archive::new(self.tar_stream()?);
+        let entries = archive.entries().map_err(|e| format!("Invalid tar archive: {}", e))?;
+        for entry in entries {
+            // Le contenu non lu d'une entrée est sauté par la bibliothèque
+            let mut entry = entry.map_err(|e| format!("Invalid tar archive: {}", e))?;
+            if let Some(description) = tar_entry(&entry) {
+                each(&description, &mut entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn lossy(bytes: Cow<'_, [u8]>) -> String {
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn zip_entry(file: &ZipFile<'_, File>) -> ArchiveEntry {
+    let kind = if file.is_symlink() {
+        EntryKind::Symlink
+    } else if file.is_dir() {
+        EntryKind::Directory
+    } else {
+        EntryKind::File
+    };
+    ArchiveEntry {
+        name: file.name().to_string(),
+        kind,
+        size: file.size(),
+        compressed_size: Some(file.compressed_size()),
+        mode: file.unix_mode(),
+        modified: zip_modified(file),
+        link_target: None,
+    }
+}
+
+// Date de modification stockée : l'horodatage étendu (UTC) s'il existe,
+// sinon la date MS-DOS, exprimée en heure locale
+fn zip_modified(file: &ZipFile<'_, File>) -> Option<SystemTime> {
+    let extended = file.extra_data_fields().find_map(|field| match field {
+        ExtraField::ExtendedTimestamp(timestamp) => timestamp.mod_time(),
+        _ => None,
+    });
+    if let Some(seconds) = extended {
+        return Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds.into()));
+    }
+
+    let dos = file.last_modified()?;
+    let local = chrono::NaiveDate::from_ymd_opt(dos.year().into(), dos.month().into(), dos.day().into())?
+        .and_hms_opt(dos.hour().into(), dos.minute().into(), dos.second().into())?
+        .and_local_timezone(chrono::Local)
+        .earliest()?;
+    Some(local.into())
+}
+
+// Les en-têtes de métadonnées (noms longs, extensions pax) ne sont pas des entrées
+fn tar_entry<R: Read>(entry: &tar::Entry<'_, R>) -> Option<ArchiveEntry> {
+    let header = entry.header();
+    let kind = match header.entry_type() {
+        tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
+        tar::EntryType::Directory => EntryKind::Directory,
+        tar::EntryType::Symlink => EntryKind::Symlink,
+        tar::EntryType::XGlobalHeader
+        | tar::EntryType::XHeader
+        | tar::EntryType::GNULongName
+        | tar::EntryType::GNULongLink => return None,
+        _ => EntryKind::Unsupported,
+    };
+    Some(ArchiveEntry {
+        name: lossy(entry.path_bytes()),
+        kind,
+        size: entry.size(),
+        compressed_size: None,
+        mode: header.mode().ok(),
+        modified: header
+            .mtime()
+            .ok()
+            .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)),
+        link_target: entry.link_name_bytes().map(lossy),
+    })
+}
+
+fn seven_z_entry(entry: &SevenZArchiveEntry) -> ArchiveEntry {
+    let mode = (entry.has_windows_attributes && entry.windows_attributes() & SEVEN_Z_UNIX_EXTENSION != 0)
+        .then(|| entry.windows_attributes() >> 16);
+    let kind = if entry.is_directory() {
+        EntryKind::Directory
+    } else if mode.is_some_and(|mode| mode & UNIX_FILE_TYPE_MASK == UNIX_SYMLINK) {
+        EntryKind::Symlink
+    } else {
+        EntryKind::File
+    };
+    ArchiveEntry {
+        name: entry.name().to_string(),
+        kind,
+        size: entry.size(),
+        compressed_size: None,
+        mode,
+        modified: entry
+            .has_last_modified_date
+            .then(|| entry.last_modified_date().into()),
+        link_target: None,
+    }
+}
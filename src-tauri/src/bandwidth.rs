@@ -0,0 +1,84 @@
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration, Instant};
+
+// Durée maximale d'une attente, pour qu'un changement de limite s'applique rapidement
+const MAX_WAIT_SLICE_MS: u64 = 100;
+
+struct BucketState {
+    limit: Option<u64>, // bytes/sec
+    // Plafond imposé par la programmation horaire, en plus de la limite de l'utilisateur
+    cap: Option<u64>,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Limiteur de bande passante (token bucket) partagé par tous les transferts
+pub struct BandwidthLimiter {
+    state: Mutex<BucketState>,
+}
+
+impl BandwidthLimiter {
+    pub fn new(limit: Option<u64>) -> Self {
+        Self {
+            state: Mutex::new(BucketState {
+                limit: limit.filter(|l| *l > 0),
+                cap: None,
+                tokens: 0.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    pub async fn limit(&self) -> Option<u64> {
+        self.state.lock().await.limit
+    }
+
+    // Modifier la limite, y compris pendant les téléchargements (None = illimité)
+    pub async fn set_limit(&self, limit: Option<u64>) {
+        let mut state = self.state.lock().await;
+        state.limit = limit.filter(|l| *l > 0);
+        state.tokens = 0.0;
+        state.last_refill = Instant::now();
+    }
+
+    // Plafonner le débit hors des plages horaires (None = pas de plafond)
+    pub async fn set_cap(&self, cap: Option<u64>) {
+        let mut state = self.state.lock().await;
+        let cap = cap.filter(|c| *c > 0);
+        if state.cap != cap {
+            state.cap = cap;
+            state.tokens = 0.0;
+            state.last_refill = Instant::now();
+        }
+    }
+
+    // Consommer `bytes` jetons, en attendant que le solde redevienne positif.
+    // Le solde peut devenir négatif : un gros bloc est payé par l'attente des suivants.
+    pub async fn acquire(&self, bytes: u64) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let limit = match (state.limit, state.cap) {
+                    (Some(limit), Some(cap)) => limit.min(cap) as f64,
+                    (Some(limit), None) | (None, Some(limit)) => limit as f64,
+                    (None, None) => return,
+                };
+
+                // Remplir le seau, plafonné à une seconde de débit
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * limit).min(limit);
+                state.last_refill = now;
+
+                if state.tokens >= 0.0 {
+                    state.tokens -= bytes as f64;
+                    return;
+                }
+
+                Duration::from_secs_f64(-state.tokens / limit)
+            };
+
+            sleep(wait.min(Duration::from_millis(MAX_WAIT_SLICE_MS))).await;
+        }
+    }
+}
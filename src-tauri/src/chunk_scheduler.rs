@@ -0,0 +1,142 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::download_manager::{ChunkProgress, ChunkStatus};
+
+/// Plage attribuée à un worker
+pub struct ChunkAssignment {
+    pub chunk: ChunkProgress,
+    // Chunk dont la fin a été raccourcie pour créer celui-ci (vol de travail)
+    pub split_from: Option<usize>,
+}
+
+struct RunningRange {
+    end: u64,
+    // Prochain octet que le worker va écrire
+    position: u64,
+}
+
+impl RunningRange {
+    fn remaining(&self) -> u64 {
+        (self.end + 1).saturating_sub(self.position)
+    }
+}
+
+struct SchedulerState {
+    pending: VecDeque<ChunkProgress>,
+    running: HashMap<usize, RunningRange>,
+    // Chunks ayant épuisé leurs tentatives, avec la dernière erreur
+    failed: Vec<(ChunkProgress, String)>,
+    next_id: usize,
+}
+
+/// File partagée des plages restantes d'un téléchargement segmenté.
+/// Un worker inactif reprend la seconde moitié de la plus grosse plage en cours.
+pub struct ChunkScheduler {
+    state: Mutex<SchedulerState>,
+    // Taille minimale d'une plage volée ; c'est aussi la distance gardée avec la position
+    // du worker volé, pour que le bloc qu'il est en train d'écrire ne déborde jamais
+    min_split: u64,
+}
+
+impl ChunkScheduler {
+    pub fn new(pending: Vec<ChunkProgress>, next_id: usize, min_split: u64) -> Self {
+        Self {
+            state: Mutex::new(SchedulerState {
+                pending: pending.into(),
+                running: HashMap::new(),
+                failed: Vec::new(),
+                next_id,
+            }),
+            min_split: min_split.max(1),
+        }
+    }
+
+    // Reste-t-il du travail pour un worker supplémentaire ?
+    pub fn has_work(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        !state.pending.is_empty() || state.running.values().any(|r| r.remaining() >= 2 * self.min_split)
+    }
+
+    // Prochaine plage : la file d'abord, puis la moitié de la plus grosse plage en cours
+    pub fn next(&self) -> Option<ChunkAssignment> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(chunk) = state.pending.pop_front() {
+            state.running.insert(
+                chunk.id,
+                RunningRange {
+                    end: chunk.end,
+                    position: chunk.start + chunk.downloaded,
+                },
+            );
+            return Some(ChunkAssignment { chunk, split_from: None });
+        }
+
+        let victim_id = state
+            .running
+            .iter()
+            .max_by_key(|(_, range)| range.remaining())
+            .map(|(id, _)| *id)?;
+        let victim = state.running.get_mut(&victim_id)?;
+        let remaining = victim.remaining();
+        if remaining < 2 * self.min_split {
+            return None;
+        }
+
+        let start = victim.position + remaining / 2;
+        let end = victim.end;
+        victim.end = start - 1;
+
+        let id = state.next_id;
+        state.next_id += 1;
+        state.running.insert(id, RunningRange { end, position: start });
+
+        Some(ChunkAssignment {
+            chunk: ChunkProgress {
+                id,
+                start,
+                end,
+                downloaded: 0,
+                status: ChunkStatus::Pending,
+                source_url: None,
+                speed: 0,
+                error: None,
+            },
+            split_from: Some(victim_id),
+        })
+    }
+
+    // Signaler la position atteinte avant d'écrire ; renvoie la fin (éventuellement raccourcie) de la plage
+    pub fn advance(&self, id: usize, position: u64) -> Option<u64> {
+        let mut state = self.state.lock().unwrap();
+        state.running.get_mut(&id).map(|range| {
+            range.position = position;
+            range.end
+        })
+    }
+
+    // Plage terminée ou abandonnée : elle ne peut plus être volée
+    pub fn finish(&self, id: usize) {
+        self.state.lock().unwrap().running.remove(&id);
+    }
+
+    // Plage en échec : mise de côté pour un nouveau passage, sans bloquer les autres.
+    // La copie du worker peut ignorer un vol récent : sa fin est ramenée à celle de la file.
+    pub fn fail(&self, mut chunk: ChunkProgress, error: String) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(range) = state.running.remove(&chunk.id) {
+            chunk.end = chunk.end.min(range.end);
+        }
+        state.failed.push((chunk, error));
+    }
+
+    pub fn take_failed(&self) -> Vec<(ChunkProgress, String)> {
+        std::mem::take(&mut self.state.lock().unwrap().failed)
+    }
+
+    // Remettre des plages en file pour un nouveau passage
+    pub fn requeue(&self, chunks: Vec<ChunkProgress>) {
+        self.state.lock().unwrap().pending.extend(chunks);
+    }
+}
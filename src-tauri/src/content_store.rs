@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Fichier présent dans le cache, identifié par son empreinte SHA-256
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub sha256: String,
+    pub path: String,
+    pub size: u64,
+    pub last_used: String,
+}
+
+/// Résultat d'un nettoyage du cache
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachePruneReport {
+    pub removed: Vec<CacheEntry>,
+    pub freed_bytes: u64,
+    pub remaining_bytes: u64,
+}
+
+/// Taille maximale du cache par défaut. Une archive supprimée après extraction reste sinon
+/// conservée indéfiniment par son objet.
+pub const DEFAULT_MAX_TOTAL_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+/// Stockage adressé par contenu : `<root>/<2 premiers caractères>/<sha256>`.
+/// Les fichiers téléchargés y sont liés (lien physique, ou copie à défaut), si bien qu'un
+/// contenu identique n'est stocké et téléchargé qu'une seule fois.
+pub struct ContentStore {
+    root: PathBuf,
+    // Budget appliqué après chaque insertion, les objets les moins récemment utilisés partant d'abord
+    max_total_bytes: u64,
+}
+
+impl ContentStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+        }
+    }
+
+    pub fn with_max_total_bytes(mut self, max_total_bytes: u64) -> Self {
+        self.max_total_bytes = max_total_bytes;
+        self
+    }
+
+    fn object_path(&self, sha256: &str) -> Option<PathBuf> {
+        if sha256.len() != 64 || !sha256.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return None;
+        }
+        Some(self.root.join(&sha256[..2]).join(sha256))
+    }
+
+    // Chemin de l'objet s'il est déjà présent
+    pub fn lookup(&self, sha256: &str) -> Option<PathBuf> {
+        self.object_path(sha256).filter(|path| path.is_file())
+    }
+
+    // Placer le contenu du cache à `file_path` ; renvoie sa taille
+    pub fn materialize(&self, sha256: &str, file_path: &Path) -> io::Result<u64> {
+        let object = self
+            .lookup(sha256)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Cache entry not found"))?;
+
+        replace_with_link(&object, file_path)?;
+        touch(&object)?;
+        Ok(fs::metadata(&object)?.len())
+    }
+
+    // Enregistrer un fichier complet et vérifié. S'il est déjà connu, le fichier devient
+    // un lien vers l'objet existant au lieu d'en être une seconde copie. Le budget est ensuite appliqué.
+    pub fn insert(&self, file_path: &Path, sha256: &str) -> io::Result<PathBuf> {
+        let object = self
+            .object_path(sha256)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid SHA-256 digest"))?;
+
+        if object.is_file() {
+            replace_with_link(&object, file_path)?;
+        } else {
+            if let Some(parent) = object.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let tmp = object.with_extension("tmp");
+            let _ = fs::remove_file(&tmp);
+            link_or_copy(file_path, &tmp)?;
+            fs::rename(&tmp, &object)?;
+        }
+
+        touch(&object)?;
+        self.prune(None, Some(self.max_total_bytes))?;
+        Ok(object)
+    }
+
+    pub fn list(&self) -> io::Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+
+        let shards = match fs::read_dir(&self.root) {
+            Ok(shards) => shards,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(entries),
+            Err(e) => return Err(e),
+        };
+
+        for shard in shards {
+            let shard = shard?;
+            if !shard.file_type()?.is_dir() {
+                continue;
+            }
+
+            for object in fs::read_dir(shard.path())? {
+                let object = object?;
+                let name = object.file_name().to_string_lossy().to_string();
+                // Ignorer les fichiers temporaires d'une insertion interrompue
+                if self.object_path(&name).is_none() {
+                    continue;
+                }
+
+                let metadata = object.metadata()?;
+                entries.push(CacheEntry {
+                    sha256: name,
+                    path: object.path().to_string_lossy().to_string(),
+                    size: metadata.len(),
+                    last_used: chrono::DateTime::<chrono::Utc>::from(metadata.modified()?).to_rfc3339(),
+                });
+            }
+        }
+
+        // Du plus récemment utilisé au plus ancien
+        entries.sort_by_key(|e| std::cmp::Reverse(last_used_at(e)));
+        Ok(entries)
+    }
+
+    // Supprimer les entrées inutilisées depuis `max_age`, puis les moins récemment
+    // utilisées jusqu'à repasser sous `max_total_bytes`
+    pub fn prune(&self, max_age: Option<Duration>, max_total_bytes: Option<u64>) -> io::Result<CachePruneReport> {
+        let mut entries = self.list()?;
+        let mut report = CachePruneReport::default();
+
+        if let Some(max_age) = max_age {
+            let cutoff = chrono::DateTime::<chrono::Utc>::from(SystemTime::now() - max_age);
+            let (expired, kept): (Vec<_>, Vec<_>) = entries
+                .into_iter()
+                .partition(|e| last_used_at(e).is_some_and(|t| t < cutoff));
+            for entry in expired {
+                self.remove(entry, &mut report)?;
+            }
+            entries = kept;
+        }
+
+        let mut total: u64 = entries.iter().map(|e| e.size).sum();
+        if let Some(max_total_bytes) = max_total_bytes {
+            while total > max_total_bytes {
+                let Some(entry) = entries.pop() else {
+                    break;
+                };
+                total -= entry.size;
+                self.remove(entry, &mut report)?;
+            }
+        }
+
+        report.remaining_bytes = total;
+        Ok(report)
+    }
+
+    fn remove(&self, entry: CacheEntry, report: &mut CachePruneReport) -> io::Result<()> {
+        fs::remove_file(&entry.path)?;
+        report.freed_bytes += entry.size;
+        report.removed.push(entry);
+        Ok(())
+    }
+}
+
+// Comparer des instants et non des chaînes : l'ordre textuel dépend du décalage et de la précision
+fn last_used_at(entry: &CacheEntry) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    chrono::DateTime::parse_from_rfc3339(&entry.last_used).ok()
+}
+
+fn link_or_copy(from: &Path, to: &Path) -> io::Result<()> {
+    // Une copie reste possible si le cache est sur un autre volume
+    if fs::hard_link(from, to).is_err() {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+// Remplacer `file_path` par l'objet sans jamais laisser de fichier à moitié écrit
+fn replace_with_link(object: &Path, file_path: &Path) -> io::Result<()> {
+    let mut tmp = file_path.as_os_str().to_owned();
+    tmp.push(".cache-tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let _ = fs::remove_file(&tmp);
+    link_or_copy(object, &tmp)?;
+    fs::rename(&tmp, file_path)?;
+    // Si `file_path` était déjà un lien vers l'objet, rename ne fait rien et laisse `tmp`
+    let _ = fs::remove_file(&tmp);
+    Ok(())
+}
+
+// La date de modification de l'objet sert de date de dernière utilisation
+fn touch(path: &Path) -> io::Result<()> {
+    fs::File::options().write(true).open(path)?.set_modified(SystemTime::now())
+}
@@ -0,0 +1,81 @@
+use log::warn;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::download_group::DownloadGroupProgress;
+use crate::download_manager::DownloadProgress;
+
+/// Événement émis par le moteur de téléchargement
+#[derive(Debug, Clone)]
+pub enum DownloadEvent {
+    Progress(DownloadProgress),
+    Completed(DownloadProgress),
+    Failed(DownloadProgress),
+    GroupProgress(DownloadGroupProgress),
+    // Émis une seule fois, quand tous les membres du groupe ont réussi.
+    // Remplace les `Completed` des membres, qui ne sont pas émis.
+    GroupCompleted(DownloadGroupProgress),
+    GroupFailed(DownloadGroupProgress),
+}
+
+impl DownloadEvent {
+    // Nom de l'événement côté frontend
+    pub fn name(&self) -> &'static str {
+        match self {
+            DownloadEvent::Progress(_) => "download-progress",
+            DownloadEvent::Completed(_) => "download-completed",
+            DownloadEvent::Failed(_) => "download-failed",
+            DownloadEvent::GroupProgress(_) => "download-group-progress",
+            DownloadEvent::GroupCompleted(_) => "download-group-completed",
+            DownloadEvent::GroupFailed(_) => "download-group-failed",
+        }
+    }
+}
+
+/// Destination des événements du moteur : webview, CLI, service ou tests
+pub trait DownloadEventSink: Send + Sync {
+    fn emit(&self, event: DownloadEvent);
+}
+
+// Moteur sans observateur
+pub struct NullEventSink;
+
+impl DownloadEventSink for NullEventSink {
+    fn emit(&self, _event: DownloadEvent) {}
+}
+
+// Canal : permet de consommer les événements sans Tauri
+impl DownloadEventSink for UnboundedSender<DownloadEvent> {
+    fn emit(&self, event: DownloadEvent) {
+        // Le récepteur peut avoir été abandonné ; le moteur continue sans lui
+        let _ = self.send(event);
+    }
+}
+
+/// Pont vers les événements Tauri écoutés par le frontend
+pub struct TauriEventSink {
+    app: AppHandle,
+}
+
+impl TauriEventSink {
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+impl DownloadEventSink for TauriEventSink {
+    fn emit(&self, event: DownloadEvent) {
+        let result = match &event {
+            DownloadEvent::Progress(progress)
+            | DownloadEvent::Completed(progress)
+            | DownloadEvent::Failed(progress) => self.app.emit(event.name(), progress),
+            DownloadEvent::GroupProgress(group)
+            | DownloadEvent::GroupCompleted(group)
+            | DownloadEvent::GroupFailed(group) => self.app.emit(event.name(), group),
+        };
+
+        if let Err(e) = result {
+            warn!("Failed to emit {} event: {}", event.name(), e);
+        }
+    }
+}
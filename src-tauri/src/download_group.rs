@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::download_manager::{DownloadProgress, DownloadStatus};
+
+/// Ensemble de téléchargements suivis comme une seule unité (jeu de base, assets, packs de voix...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadGroup {
+    pub id: String,
+    pub name: Option<String>,
+    pub created_at: String,
+    // Renseigné une seule fois, quand tous les membres ont réussi
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    // L'échec du groupe n'est signalé qu'une fois
+    #[serde(default)]
+    pub failure_reported: bool,
+}
+
+/// Progression agrégée des membres d'un groupe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadGroupProgress {
+    pub id: String,
+    pub name: Option<String>,
+    pub download_ids: Vec<String>,
+    pub total_size: Option<u64>, // None si la taille d'un membre est inconnue
+    pub downloaded: u64,
+    pub speed: u64, // bytes/sec
+    pub eta_seconds: Option<u64>,
+    pub percentage: f64,
+    pub status: DownloadStatus,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+impl DownloadGroupProgress {
+    pub fn aggregate(group: &DownloadGroup, members: &[&DownloadProgress]) -> Self {
+        let total_size = members
+            .iter()
+            .map(|m| m.total_size)
+            .sum::<Option<u64>>();
+        let downloaded = members.iter().map(|m| m.downloaded).sum();
+        let speed = members.iter().map(|m| m.speed).sum();
+
+        let percentage = match total_size {
+            Some(total_size) if total_size > 0 => (downloaded as f64 / total_size as f64) * 100.0,
+            _ => 0.0,
+        };
+        let eta_seconds = match total_size {
+            Some(total_size) if speed > 0 => Some(total_size.saturating_sub(downloaded).div_ceil(speed)),
+            _ => None,
+        };
+
+        Self {
+            id: group.id.clone(),
+            name: group.name.clone(),
+            download_ids: members.iter().map(|m| m.id.clone()).collect(),
+            total_size,
+            downloaded,
+            speed,
+            eta_seconds,
+            percentage,
+            status: group_status(members),
+            created_at: group.created_at.clone(),
+            completed_at: group.completed_at.clone(),
+        }
+    }
+}
+
+// Un membre en échec fait échouer le groupe ; le groupe n'est terminé que si tous ont réussi
+fn group_status(members: &[&DownloadProgress]) -> DownloadStatus {
+    let any = |status: DownloadStatus| members.iter().any(|m| m.status == status);
+
+    if !members.is_empty() && members.iter().all(|m| m.status == DownloadStatus::Completed) {
+        DownloadStatus::Completed
+    } else if any(DownloadStatus::Failed) {
+        DownloadStatus::Failed
+    } else if any(DownloadStatus::Cancelled) {
+        DownloadStatus::Cancelled
+    } else if any(DownloadStatus::Downloading) {
+        DownloadStatus::Downloading
+    } else if any(DownloadStatus::Paused) {
+        DownloadStatus::Paused
+    } else {
+        DownloadStatus::Pending
+    }
+}
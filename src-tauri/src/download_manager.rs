@@ -1,22 +1,35 @@
-use reqwest::{Client, header::{RANGE, CONTENT_LENGTH}};
+use futures::future::BoxFuture;
+use log::{info, warn};
+use reqwest::{Client, StatusCode, header::{HeaderMap, HeaderName, HeaderValue, RANGE, CONTENT_LENGTH, CONTENT_RANGE, ACCEPT_RANGES, ETAG, LAST_MODIFIED, IF_RANGE}};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, State};
 use tokio::fs::{File, OpenOptions};
-use tokio::io::{AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
 use tokio::sync::RwLock;
-use tokio::time::{sleep, Duration, Instant};
+use tokio::time::{interval, sleep, Duration, Instant, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use crate::bandwidth::BandwidthLimiter;
+use crate::chunk_scheduler::ChunkScheduler;
+use crate::content_store::{CacheEntry, CachePruneReport, ContentStore};
+use crate::download_schedule::{self, DownloadSchedule, ScheduleState};
+use crate::download_group::{DownloadGroup, DownloadGroupProgress};
+use crate::download_events::{DownloadEvent, DownloadEventSink, NullEventSink, TauriEventSink};
+use crate::retry::{self, RetryPolicy};
+use crate::throughput::ThroughputMeter;
+
 // Structures de données
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadProgress {
     pub id: String,
     pub url: String,
     pub file_path: String,
-    pub total_size: u64,
+    pub total_size: Option<u64>, // None : taille inconnue (progression indéterminée)
     pub downloaded: u64,
     pub speed: u64, // bytes/sec
     pub percentage: f64,
@@ -26,6 +39,124 @@ pub struct DownloadProgress {
     pub created_at: String,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
+    // Temps restant estimé à partir du débit récent
+    #[serde(default)]
+    pub eta_seconds: Option<u64>,
+    // Validateurs HTTP capturés au démarrage pour détecter un changement de la ressource
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    // SHA-256 attendu : Completed signifie alors "téléchargé et vérifié"
+    #[serde(default)]
+    pub expected_sha256: Option<String>,
+    #[serde(default)]
+    pub failure_reason: Option<FailureReason>,
+    // Plages d'octets qui n'ont pas pu être récupérées
+    #[serde(default)]
+    pub failed_ranges: Vec<FailedRange>,
+    // Priorité dans la file d'attente (la plus haute démarre en premier)
+    #[serde(default)]
+    pub priority: i32,
+    // Miroirs du même contenu, essayés dans l'ordre après `url`
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    // Source ayant fourni etag/last_modified (par défaut `url`)
+    #[serde(default)]
+    pub validator_url: Option<String>,
+    // Headers ajoutés aux requêtes vers l'origine de `url` (ex. Authorization). Ils peuvent contenir
+    // des secrets : ni journalisés ni émis dans les événements, ils sont à refournir après un redémarrage
+    #[serde(skip)]
+    pub headers: HashMap<String, String>,
+    // Groupe (release en plusieurs archives) auquel appartient ce téléchargement
+    #[serde(default)]
+    pub group_id: Option<String>,
+    // Fichier fourni par le cache, sans aucun transfert
+    #[serde(default)]
+    pub from_cache: bool,
+    // Heure programmée du démarrage (RFC 3339) ; le téléchargement attend en file jusque-là
+    #[serde(default)]
+    pub start_at: Option<String>,
+    // Réglages propres à ce téléchargement
+    #[serde(default)]
+    pub overrides: DownloadOverrides,
+    // Incrémenté à chaque fois que le fichier est vidé pour repartir de zéro : le hachage
+    // au fil de l'eau recommence alors depuis le début
+    #[serde(skip)]
+    pub(crate) reset_generation: u64,
+}
+
+impl DownloadProgress {
+    // Toutes les sources du contenu, par ordre de préférence
+    fn sources(&self) -> Vec<String> {
+        let mut sources = vec![self.url.clone()];
+        for mirror in &self.mirrors {
+            if !sources.contains(mirror) {
+                sources.push(mirror.clone());
+            }
+        }
+        sources
+    }
+
+    fn validator_source(&self) -> &str {
+        self.validator_url.as_deref().unwrap_or(&self.url)
+    }
+
+    // Headers validés au démarrage du téléchargement
+    fn request_headers(&self) -> HeaderMap {
+        parse_headers(&self.headers).unwrap_or_default()
+    }
+
+    // Headers à envoyer à une source : aucun pour un miroir d'une autre origine
+    fn source_headers(&self, source_url: &str) -> HeaderMap {
+        scoped_headers(&self.url, source_url, &self.request_headers())
+    }
+
+    // Appliquer un débit mesuré et en déduire le temps restant
+    fn set_throughput(&mut self, speed: u64) {
+        self.speed = speed;
+        self.eta_seconds = match self.total_size {
+            Some(total_size) if speed > 0 => Some(total_size.saturating_sub(self.downloaded).div_ceil(speed)),
+            _ => None,
+        };
+    }
+
+    fn update_percentage(&mut self) {
+        self.percentage = match self.total_size {
+            Some(total_size) if total_size > 0 => (self.downloaded as f64 / total_size as f64) * 100.0,
+            _ => 0.0,
+        };
+    }
+}
+
+// Paramètres d'un nouveau téléchargement
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DownloadRequest {
+    pub url: String,
+    pub file_path: String,
+    pub mirrors: Vec<String>,
+    pub headers: HashMap<String, String>,
+    pub expected_sha256: Option<String>,
+    pub priority: i32,
+    // Démarrage différé (RFC 3339)
+    pub start_at: Option<String>,
+    pub overrides: DownloadOverrides,
+}
+
+// Plage d'octets (bornes incluses) abandonnée après toutes les tentatives
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FailedRange {
+    pub start: u64,
+    pub end: u64,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FailureReason {
+    Transfer,
+    HashMismatch,
+    InsufficientSpace,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -45,6 +176,15 @@ pub struct ChunkProgress {
     pub end: u64,
     pub downloaded: u64,
     pub status: ChunkStatus,
+    // Miroir qui sert (ou a servi) ce chunk
+    #[serde(default)]
+    pub source_url: Option<String>,
+    // Débit récent de la connexion du chunk : 0 pendant un transfert signale une connexion bloquée
+    #[serde(default)]
+    pub speed: u64, // bytes/sec
+    // Dernière erreur du chunk, effacée quand il se termine
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -55,16 +195,104 @@ pub enum ChunkStatus {
     Failed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Un champ absent du fichier de configuration prend sa valeur par défaut
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct DownloadConfig {
     pub max_concurrent_chunks: usize,
     pub chunk_size: u64,
     pub max_retries: u32,
+    // Délai avant la première nouvelle tentative, doublé à chaque échec jusqu'à `max_retry_delay_ms`
     pub retry_delay_ms: u64,
-    pub timeout_seconds: u64,
+    pub max_retry_delay_ms: u64,
+    // Établissement de la connexion (TCP, TLS, proxy)
+    pub connect_timeout_seconds: u64,
+    // Délai maximal sans recevoir le moindre octet : un transfert lent mais actif n'expire pas
+    pub read_timeout_seconds: u64,
+    // Durée maximale d'une requête complète (None = illimitée)
+    pub total_timeout_seconds: Option<u64>,
     pub progress_update_interval_ms: u64,
+    // Débit maximal partagé par tous les téléchargements (None = illimité)
+    pub max_bytes_per_second: Option<u64>,
+    // Nombre de téléchargements actifs simultanément, les autres restent en file (Pending)
+    pub max_concurrent_downloads: usize,
+    // Répartir les chunks entre les miroirs au lieu de tous commencer par le premier
+    pub spread_chunks_across_mirrors: bool,
+    // Espace libre exigé en plus du fichier, pour pouvoir l'extraire ensuite
+    pub disk_space_margin_bytes: u64,
+    // Proxy et certificats, modifiables aussi avec `set_network_config`
+    pub network: NetworkConfig,
+}
+
+impl DownloadConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrent_chunks == 0 || self.max_concurrent_downloads == 0 || self.max_retries == 0 {
+            return Err("Concurrency limits and max_retries must be at least 1".to_string());
+        }
+        if self.chunk_size == 0 || self.progress_update_interval_ms == 0 {
+            return Err("chunk_size and progress_update_interval_ms must be greater than zero".to_string());
+        }
+        if self.connect_timeout_seconds == 0
+            || self.read_timeout_seconds == 0
+            || self.total_timeout_seconds == Some(0)
+        {
+            return Err("Timeouts must be greater than zero".to_string());
+        }
+        if self.max_bytes_per_second == Some(0) {
+            return Err("Bandwidth limit must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new(self.retry_delay_ms, self.max_retry_delay_ms)
+    }
+}
+
+// Réglages propres à un téléchargement ; un champ absent suit la configuration globale.
+// Le délai de connexion dépend du client HTTP partagé et n'est pas surchargeable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DownloadOverrides {
+    pub max_concurrent_chunks: Option<usize>,
+    pub chunk_size: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub retry_delay_ms: Option<u64>,
+    pub read_timeout_seconds: Option<u64>,
+    pub total_timeout_seconds: Option<u64>,
+}
+
+impl DownloadOverrides {
+    pub fn apply(&self, config: &DownloadConfig) -> DownloadConfig {
+        DownloadConfig {
+            max_concurrent_chunks: self.max_concurrent_chunks.unwrap_or(config.max_concurrent_chunks),
+            chunk_size: self.chunk_size.unwrap_or(config.chunk_size),
+            max_retries: self.max_retries.unwrap_or(config.max_retries),
+            retry_delay_ms: self.retry_delay_ms.unwrap_or(config.retry_delay_ms),
+            read_timeout_seconds: self.read_timeout_seconds.unwrap_or(config.read_timeout_seconds),
+            total_timeout_seconds: self.total_timeout_seconds.or(config.total_timeout_seconds),
+            ..config.clone()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.apply(&DownloadConfig::default()).validate()
+    }
 }
 
+// Réglages réseau appliqués à toutes les requêtes (sondes HEAD/GET et transferts)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NetworkConfig {
+    // http://, https://, socks5:// ou socks5h:// (None = proxy système)
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    // Hôtes ou domaines contactés sans proxy (ex. "localhost", ".studio.internal", "10.0.0.0/8")
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    // Certificats racine supplémentaires (fichiers PEM)
+    #[serde(default)]
+    pub ca_certificates: Vec<String>,
+}
 impl Default for DownloadConfig {
     fn default() -> Self {
         Self {
@@ -72,674 +300,3007 @@ impl Default for DownloadConfig {
             chunk_size: 2 * 1024 * 1024, // 2MB
             max_retries: 5,
             retry_delay_ms: 2000,
-            timeout_seconds: 60,
+            max_retry_delay_ms: 60_000,
+            connect_timeout_seconds: 15,
+            read_timeout_seconds: 60,
+            total_timeout_seconds: None,
             progress_update_interval_ms: 100,
+            max_bytes_per_second: None,
+            max_concurrent_downloads: 2,
+            spread_chunks_across_mirrors: false,
+            disk_space_margin_bytes: 512 * 1024 * 1024, // 512MB pour l'extraction
+            network: NetworkConfig::default(),
         }
     }
 }
 
-// Gestionnaire principal des téléchargements
-pub struct DownloadManager {
-    downloads: Arc<RwLock<HashMap<String, DownloadProgress>>>,
-    client: Client,
-    config: DownloadConfig,
-    active_tasks: Arc<RwLock<HashMap<String, tokio::task::JoinHandle<()>>>>,
+// Journal des téléchargements persisté sur disque
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JournalFile {
+    version: u32,
+    downloads: Vec<DownloadProgress>,
+    #[serde(default)]
+    groups: Vec<DownloadGroup>,
+    #[serde(default)]
+    schedule: DownloadSchedule,
+    // Ordre de la file, réordonnancements manuels compris
+    #[serde(default)]
+    queue: Vec<String>,
 }
 
-impl DownloadManager {
-    pub fn new(config: DownloadConfig) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.timeout_seconds))
-            .user_agent("Lysandra-Launcher/1.0")
-            .build()
-            .expect("Failed to create HTTP client");
+const JOURNAL_VERSION: u32 = 1;
+const JOURNAL_WRITE_INTERVAL_MS: u128 = 1000;
+
+/// Persiste l'état des téléchargements pour pouvoir les reprendre après un redémarrage
+struct DownloadJournal {
+    path: PathBuf,
+    write_lock: tokio::sync::Mutex<()>,
+    last_write: std::sync::Mutex<Instant>,
+}
 
+impl DownloadJournal {
+    fn new(path: PathBuf) -> Self {
         Self {
-            downloads: Arc::new(RwLock::new(HashMap::new())),
-            client,
-            config,
-            active_tasks: Arc::new(RwLock::new(HashMap::new())),
+            path,
+            write_lock: tokio::sync::Mutex::new(()),
+            last_write: std::sync::Mutex::new(Instant::now()),
         }
     }
 
-    // Démarrer un nouveau téléchargement
-    pub async fn start_download(
-        &self,
-        url: String,
-        file_path: String,
-        app: AppHandle,
-    ) -> Result<String, String> {
-        let download_id = Uuid::new_v4().to_string();
-        
-        // Vérifier si le fichier existe déjà et est complet
-        if let Ok(metadata) = tokio::fs::metadata(&file_path).await {
-            let existing_size = metadata.len();
-            
-            // Obtenir la taille du fichier distant
-            match self.get_file_size(&url).await {
-                Ok(remote_size) if existing_size == remote_size => {
-                    return Err("File already exists and is complete".to_string());
+    // Charger les enregistrements (appelé au setup, avant le démarrage de toute tâche)
+    fn load(path: &Path) -> JournalFile {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return JournalFile::default(),
+        };
+
+        let mut journal: JournalFile = match serde_json::from_str(&content) {
+            Ok(journal) => journal,
+            Err(e) => {
+                warn!("Ignoring unreadable download journal {:?}: {}", path, e);
+                return JournalFile::default();
+            }
+        };
+
+        for progress in journal.downloads.iter_mut() {
+            // Aucune tâche ne tourne encore : les téléchargements interrompus deviennent reprenables.
+            // Ceux en file (ou programmés) y retournent et démarreront d'eux-mêmes.
+            if progress.status == DownloadStatus::Downloading {
+                progress.status = DownloadStatus::Paused;
+            }
+            progress.set_throughput(0);
+            for chunk in progress.chunks.iter_mut() {
+                chunk.speed = 0;
+                if chunk.status != ChunkStatus::Completed {
+                    chunk.status = ChunkStatus::Pending;
                 }
-                _ => {} // Continue with download
             }
         }
-        
-        // Vérifier si le serveur supporte les requêtes partielles
-        let supports_partial = self.check_partial_support(&url).await.unwrap_or(false);
-        
-        // Obtenir la taille du fichier
-        let total_size = self.get_file_size(&url).await?;
-        
-        // Vérifier si un téléchargement partiel existe
-        let existing_size = self.get_existing_file_size(&file_path).await;
-        
-        // Créer la structure de progression
-        let progress = DownloadProgress {
-            id: download_id.clone(),
-            url: url.clone(),
-            file_path: file_path.clone(),
-            total_size,
-            downloaded: existing_size,
-            speed: 0,
-            percentage: if total_size > 0 { (existing_size as f64 / total_size as f64) * 100.0 } else { 0.0 },
-            status: DownloadStatus::Pending,
-            error: None,
-            chunks: Vec::new(),
-            created_at: chrono::Utc::now().to_rfc3339(),
-            started_at: None,
-            completed_at: None,
-        };
 
-        // Ajouter aux téléchargements actifs
-        {
-            let mut downloads = self.downloads.write().await;
-            downloads.insert(download_id.clone(), progress.clone());
+        journal
+    }
+
+    async fn save(&self, journal: JournalFile) -> Result<(), String> {
+        let _guard = self.write_lock.lock().await;
+
+        let content = serde_json::to_string_pretty(&journal).map_err(|e| e.to_string())?;
+
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
         }
 
-        // Démarrer le téléchargement en arrière-plan
-        let manager = self.clone();
-        let id = download_id.clone();
-        let task = tokio::spawn(async move {
-            manager.execute_download(id, supports_partial, app).await;
-        });
+        // Écriture atomique : fichier temporaire puis renommage
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, content).await.map_err(|e| e.to_string())?;
+        tokio::fs::rename(&tmp_path, &self.path).await.map_err(|e| e.to_string())?;
 
-        // Sauvegarder la tâche pour pouvoir l'annuler plus tard
-        {
-            let mut tasks = self.active_tasks.write().await;
-            tasks.insert(download_id.clone(), task);
+        *self.last_write.lock().unwrap() = Instant::now();
+        Ok(())
+    }
+
+    fn is_due(&self) -> bool {
+        self.last_write.lock().unwrap().elapsed().as_millis() >= JOURNAL_WRITE_INTERVAL_MS
+    }
+}
+
+// Informations obtenues par la requête HEAD (ou GET à défaut)
+struct RemoteInfo {
+    url: String,
+    total_size: Option<u64>,
+    supports_partial: bool,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl RemoteInfo {
+    // La ressource distante est-elle toujours celle des octets déjà téléchargés ?
+    // Les validateurs ne sont comparables que pour la source qui les a fournis.
+    fn matches(&self, progress: &DownloadProgress) -> bool {
+        if self.url != progress.validator_source() {
+            return self.total_size == progress.total_size;
         }
 
-        Ok(download_id)
+        self.total_size == progress.total_size
+            && (progress.etag.is_none() || self.etag == progress.etag)
+            && (progress.last_modified.is_none() || self.last_modified == progress.last_modified)
     }
+}
 
-    // Exécuter le téléchargement avec la stratégie appropriée
-    async fn execute_download(&self, download_id: String, supports_partial: bool, app: AppHandle) {
-        let total_size = {
-            let downloads = self.downloads.read().await;
-            downloads.get(&download_id).map(|p| p.total_size).unwrap_or(0)
-        };
+// Une URL capable de servir le contenu
+#[derive(Clone)]
+struct TransferSource {
+    url: String,
+    // Headers du téléchargement, limités à l'origine de l'URL principale
+    headers: HeaderMap,
+    // Source des validateurs : une réponse 200 à une requête partielle signifie un changement
+    validated: bool,
+    // Valeur du header If-Range envoyée avec chaque requête partielle
+    if_range: Option<String>,
+}
 
-        // Marquer comme démarré
-        {
-            let mut downloads = self.downloads.write().await;
-            if let Some(progress) = downloads.get_mut(&download_id) {
-                progress.status = DownloadStatus::Downloading;
-                progress.started_at = Some(chrono::Utc::now().to_rfc3339());
+impl TransferSource {
+    fn ranged_get(&self, client: &Client, range: String) -> reqwest::RequestBuilder {
+        let request = client.get(&self.url).headers(self.headers.clone()).header(RANGE, range);
+        match &self.if_range {
+            Some(validator) => request.header(IF_RANGE, validator),
+            None => request,
+        }
+    }
+
+    // Interpréter le statut d'une requête partielle
+    fn check_partial_response(&self, response: &reqwest::Response, total_size: Option<u64>) -> Result<(), TransferError> {
+        if response.status() == StatusCode::OK {
+            // Avec If-Range, une réponse 200 signifie que la ressource a changé
+            if self.validated {
+                return Err(TransferError::ResourceChanged);
             }
+            return Err(TransferError::SourceUnusable(format!("Mirror {} ignored the range request", self.url)));
         }
 
-        let result = if supports_partial && total_size > self.config.chunk_size {
-            self.download_with_chunks(download_id.clone(), app.clone()).await
-        } else {
-            self.download_single_thread(download_id.clone(), app.clone()).await
-        };
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(TransferError::from_response(response));
+        }
 
-        // Nettoyer la tâche
-        {
-            let mut tasks = self.active_tasks.write().await;
-            tasks.remove(&download_id);
+        let served_size = content_range_total(response);
+        if let (Some(served_size), Some(total_size)) = (served_size, total_size) {
+            if served_size != total_size {
+                return Err(TransferError::SourceUnusable(format!(
+                    "{} serves a different file ({} bytes instead of {})",
+                    self.url, served_size, total_size
+                )));
+            }
         }
 
-        // Gérer le résultat
-        match result {
-            Ok(_) => {
-                let mut downloads = self.downloads.write().await;
-                if let Some(progress) = downloads.get_mut(&download_id) {
-                    progress.status = DownloadStatus::Completed;
-                    progress.percentage = 100.0;
-                    progress.completed_at = Some(chrono::Utc::now().to_rfc3339());
-                    
-                    let _ = app.emit("download-completed", &*progress);
+        Ok(())
+    }
+}
+
+// Taille totale annoncée par Content-Range: bytes <début>-<fin>/<taille totale>
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit('/').next())
+        .and_then(|v| v.parse().ok())
+}
+
+// Cible d'un transfert partagée par les tentatives et les chunks
+#[derive(Clone)]
+struct TransferTarget {
+    sources: Vec<TransferSource>,
+    file_path: String,
+    total_size: Option<u64>,
+    // Configuration globale complétée par les réglages du téléchargement
+    config: DownloadConfig,
+    // Hachage au fil de l'eau alimenté par les écrivains, et génération du fichier qu'ils écrivent
+    hasher: Option<SharedHasher>,
+    generation: u64,
+}
+
+impl TransferTarget {
+    fn from_progress(progress: &DownloadProgress, config: &DownloadConfig) -> Self {
+        // Un ETag faible ne peut pas servir de validateur If-Range
+        let if_range = progress
+            .etag
+            .clone()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or_else(|| progress.last_modified.clone());
+
+        let sources = progress
+            .sources()
+            .into_iter()
+            .map(|url| {
+                let validated = url == progress.validator_source();
+                TransferSource {
+                    if_range: if validated { if_range.clone() } else { None },
+                    validated,
+                    headers: progress.source_headers(&url),
+                    url,
                 }
+            })
+            .collect();
+
+        Self {
+            sources,
+            file_path: progress.file_path.clone(),
+            total_size: progress.total_size,
+            config: progress.overrides.apply(config),
+            hasher: None,
+            generation: progress.reset_generation,
+        }
+    }
+
+    // Hacher les octets que cet écrivain vient d'écrire à `offset`. Ceux qu'il a écrits depuis
+    // `written_from` avant que le hachage n'atteigne sa plage (plage terminée hors ordre) sont relus sur disque.
+    async fn hash_written(&self, file: &mut File, written_from: u64, offset: u64, data: &[u8], generation: u64) {
+        let Some(hasher) = &self.hasher else {
+            return;
+        };
+        let mut hasher = hasher.lock().await;
+        if !hasher.follow(generation) {
+            return;
+        }
+
+        // En cas d'échec, la tâche de rattrapage ou la vérification finale relira ces octets
+        if (written_from..offset).contains(&hasher.offset) {
+            if file.flush().await.is_err() {
+                return;
             }
-            Err(e) => {
-                let mut downloads = self.downloads.write().await;
-                if let Some(progress) = downloads.get_mut(&download_id) {
-                    progress.status = DownloadStatus::Failed;
-                    progress.error = Some(e);
-                    
-                    let _ = app.emit("download-failed", &*progress);
-                }
+            if hasher.advance(&self.file_path, offset, generation).await.is_err() {
+                return;
             }
         }
+        hasher.update(offset, data);
     }
 
-    // Téléchargement avec chunks parallèles
-    async fn download_with_chunks(&self, download_id: String, app: AppHandle) -> Result<(), String> {
-        let (url, file_path, total_size, existing_size) = {
-            let downloads = self.downloads.read().await;
-            let progress = downloads.get(&download_id).ok_or("Download not found")?;
-            (
-                progress.url.clone(),
-                progress.file_path.clone(),
-                progress.total_size,
-                progress.downloaded,
-            )
+    // GET vers une source, partiel si `range` est fourni, borné par le délai total éventuel
+    fn request(&self, client: &Client, source: &TransferSource, range: Option<String>) -> reqwest::RequestBuilder {
+        let request = match range {
+            Some(range) => source.ranged_get(client, range),
+            None => client.get(&source.url).headers(source.headers.clone()),
         };
+        with_total_timeout(request, &self.config)
+    }
 
-        // S'assurer que le répertoire parent existe
-        if let Some(parent) = Path::new(&file_path).parent() {
-            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
-        }
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, TransferError> {
+        send_within(request, &self.config).await
+    }
 
-        // Calculer les chunks
-        let remaining_size = total_size - existing_size;
-        if remaining_size == 0 {
-            return Ok(());
+    fn read_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.read_timeout_seconds)
+    }
+
+    // Index de la source par laquelle commencer
+    fn source_index(&self, url: Option<&str>) -> Option<usize> {
+        url.and_then(|url| self.sources.iter().position(|s| s.url == url))
+    }
+}
+
+fn idle_timeout_error(idle: Duration) -> TransferError {
+    format!("No data received for {} seconds", idle.as_secs()).into()
+}
+
+// Borner une requête (en-têtes et corps) par le délai total éventuel
+fn with_total_timeout(request: reqwest::RequestBuilder, config: &DownloadConfig) -> reqwest::RequestBuilder {
+    match config.total_timeout_seconds {
+        Some(seconds) => request.timeout(Duration::from_secs(seconds)),
+        None => request,
+    }
+}
+
+// Attendre les en-têtes de la réponse, sans dépasser le délai d'inactivité : le client
+// n'a qu'un délai de connexion, un serveur muet bloquerait sinon indéfiniment
+async fn send_within(request: reqwest::RequestBuilder, config: &DownloadConfig) -> Result<reqwest::Response, TransferError> {
+    let idle = Duration::from_secs(config.read_timeout_seconds);
+    match tokio::time::timeout(idle, request.send()).await {
+        Ok(response) => response.map_err(|e| e.to_string().into()),
+        Err(_) => Err(idle_timeout_error(idle)),
+    }
+}
+
+// Erreurs d'un transfert
+#[derive(Debug)]
+pub enum TransferError {
+    // Le serveur a renvoyé la ressource complète au lieu de la plage demandée
+    ResourceChanged,
+    HashMismatch { expected: String, actual: String },
+    // Le volume cible ne peut pas contenir le fichier (marge d'extraction comprise)
+    InsufficientSpace { required: u64, available: u64 },
+    // Des plages restent manquantes après les nouveaux passages
+    ChunksFailed(Vec<FailedRange>),
+    // Réponse HTTP en erreur, avec le délai éventuellement demandé par le serveur (429, 503)
+    Http { status: StatusCode, retry_after: Option<Duration> },
+    // La source ne peut pas servir ce contenu (plages ignorées, autre fichier) : inutile d'insister
+    SourceUnusable(String),
+    Failed(String),
+}
+
+impl TransferError {
+    fn from_response(response: &reqwest::Response) -> Self {
+        TransferError::Http {
+            status: response.status(),
+            retry_after: retry::parse_retry_after(response.headers(), chrono::Utc::now()),
         }
+    }
 
-        let chunk_count = std::cmp::min(
-            self.config.max_concurrent_chunks,
-            ((remaining_size / self.config.chunk_size) + 1) as usize
-        );
-        let chunk_size = remaining_size / chunk_count as u64;
+    fn reason(&self) -> FailureReason {
+        match self {
+            TransferError::HashMismatch { .. } => FailureReason::HashMismatch,
+            TransferError::InsufficientSpace { .. } => FailureReason::InsufficientSpace,
+            _ => FailureReason::Transfer,
+        }
+    }
 
-        // Créer les chunks
-        let mut chunks = Vec::new();
-        for i in 0..chunk_count {
-            let start = existing_size + (i as u64 * chunk_size);
-            let end = if i == chunk_count - 1 {
-                total_size - 1
-            } else {
-                start + chunk_size - 1
-            };
+    // Échec qui se reproduira à chaque tentative sur la même source (404, 403...).
+    // Les erreurs réseau et d'entrée/sortie sont considérées comme passagères.
+    fn is_permanent(&self) -> bool {
+        match self {
+            TransferError::Http { status, .. } => !retry::is_transient_status(*status),
+            TransferError::SourceUnusable(_) => true,
+            _ => false,
+        }
+    }
 
-            chunks.push(ChunkProgress {
-                id: i,
-                start,
-                end,
-                downloaded: 0,
-                status: ChunkStatus::Pending,
-            });
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            TransferError::Http { retry_after, .. } => *retry_after,
+            _ => None,
         }
+    }
+}
 
-        // Ajouter les chunks à la progression
-        {
-            let mut downloads = self.downloads.write().await;
-            if let Some(progress) = downloads.get_mut(&download_id) {
-                progress.chunks = chunks.clone();
+impl std::fmt::Display for TransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferError::ResourceChanged => write!(f, "Remote file changed during download"),
+            TransferError::HashMismatch { expected, actual } => {
+                write!(f, "SHA-256 mismatch: expected {}, got {}", expected, actual)
             }
+            TransferError::InsufficientSpace { required, available } => {
+                write!(f, "Insufficient disk space: {} bytes required, {} bytes available", required, available)
+            }
+            TransferError::ChunksFailed(ranges) => {
+                let ranges: Vec<String> = ranges.iter().map(|r| format!("{}-{}", r.start, r.end)).collect();
+                write!(f, "Failed to download byte ranges {}", ranges.join(", "))
+            }
+            TransferError::Http { status, .. } => write!(f, "HTTP error: {}", status),
+            TransferError::SourceUnusable(e) | TransferError::Failed(e) => write!(f, "{}", e),
         }
+    }
+}
 
-        // Lancer les téléchargements de chunks en parallèle
-        let mut handles = Vec::new();
-        for chunk in chunks {
-            let manager = self.clone();
-            let id = download_id.clone();
-            let url_clone = url.clone();
-            let file_path_clone = file_path.clone();
-            let app_clone = app.clone();
+impl From<String> for TransferError {
+    fn from(e: String) -> Self {
+        TransferError::Failed(e)
+    }
+}
 
-            let handle = tokio::spawn(async move {
-                manager.download_chunk(id, chunk, url_clone, file_path_clone, app_clone).await
-            });
+impl From<&str> for TransferError {
+    fn from(e: &str) -> Self {
+        TransferError::Failed(e.to_string())
+    }
+}
+
+// Hachage SHA-256 du fichier au fil du téléchargement, dans l'ordre des octets
+struct StreamingHasher {
+    hasher: Sha256,
+    offset: u64,
+    // `reset_generation` du téléchargement au moment où le hachage a commencé
+    generation: u64,
+}
 
-            handles.push(handle);
+impl StreamingHasher {
+    fn new(generation: u64) -> Self {
+        Self {
+            hasher: Sha256::new(),
+            offset: 0,
+            generation,
         }
+    }
 
-        // Attendre tous les chunks
-        let mut has_error = false;
-        for handle in handles {
-            if let Err(e) = handle.await {
-                eprintln!("Chunk download error: {:?}", e);
-                has_error = true;
-            }
+    // Suivre la génération du fichier : une génération plus récente signifie que le fichier a été
+    // vidé depuis (tout est rehaché), une plus ancienne vient d'un écrivain périmé (ignoré)
+    fn follow(&mut self, generation: u64) -> bool {
+        if generation > self.generation {
+            *self = Self::new(generation);
         }
+        generation == self.generation
+    }
 
-        if has_error {
-            return Err("One or more chunks failed to download".to_string());
+    // Hacher des octets reçus : seuls ceux qui prolongent le préfixe déjà haché comptent
+    fn update(&mut self, offset: u64, data: &[u8]) {
+        let end = offset + data.len() as u64;
+        if offset <= self.offset && end > self.offset {
+            self.hasher.update(&data[(self.offset - offset) as usize..]);
+            self.offset = end;
+        }
+    }
+
+    // Hacher les octets [offset, end) déjà écrits sur disque (encore dans le cache du système)
+    async fn advance(&mut self, file_path: &str, end: u64, generation: u64) -> Result<(), String> {
+        if !self.follow(generation) || end <= self.offset {
+            return Ok(());
+        }
+
+        let mut file = File::open(file_path).await.map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(self.offset)).await.map_err(|e| e.to_string())?;
+
+        let mut buffer = vec![0u8; 256 * 1024];
+        while self.offset < end {
+            let to_read = std::cmp::min(buffer.len() as u64, end - self.offset) as usize;
+            let n = file.read(&mut buffer[..to_read]).await.map_err(|e| e.to_string())?;
+            if n == 0 {
+                break;
+            }
+            self.hasher.update(&buffer[..n]);
+            self.offset += n as u64;
         }
 
         Ok(())
     }
 
-    // Téléchargement d'un chunk spécifique
-    async fn download_chunk(
+    fn finalize(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+type SharedHasher = Arc<tokio::sync::Mutex<StreamingHasher>>;
+
+// Hachage en cours : les écrivains l'alimentent, une tâche de fond rattrape sur disque les plages hors ordre
+struct HashingTask {
+    hasher: SharedHasher,
+    stop: CancellationToken,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl HashingTask {
+    // Arrêter le rattrapage et récupérer l'état du hachage
+    async fn finish(self) -> StreamingHasher {
+        self.stop.cancel();
+        let _ = self.handle.await;
+        let mut hasher = self.hasher.lock().await;
+        std::mem::replace(&mut *hasher, StreamingHasher::new(0))
+    }
+}
+
+// Intervalle minimal entre deux journalisations d'une erreur du hachage au fil de l'eau
+const HASH_ERROR_LOG_INTERVAL_SECS: u64 = 30;
+
+// Nombre de redémarrages depuis zéro autorisés quand la ressource change
+const MAX_RESTARTS: u32 = 2;
+
+// Fenêtre glissante utilisée pour estimer le débit
+const THROUGHPUT_WINDOW_SECS: u64 = 5;
+
+// Connexions ouvertes au démarrage d'un téléchargement segmenté ; les suivantes sont
+// ajoutées tant qu'elles augmentent le débit global
+const INITIAL_CHUNK_WORKERS: usize = 2;
+const CHUNK_SCALE_INTERVAL_MS: u64 = 2000;
+
+// Fréquence de vérification des plages horaires et des démarrages programmés
+const SCHEDULE_CHECK_INTERVAL_SECS: u64 = 30;
+
+// Nombre de plages planifiées par connexion possible, pour équilibrer la charge
+const CHUNKS_PER_WORKER: u64 = 4;
+
+// Nouveaux passages sur les seules plages en échec avant d'abandonner
+const FAILED_RANGE_RETRY_ROUNDS: u32 = 2;
+
+// Tâche de téléchargement en cours, son jeton d'arrêt coopératif et son débit mesuré
+struct ActiveTask {
+    handle: tokio::task::JoinHandle<()>,
+    cancel: CancellationToken,
+    meter: Arc<ThroughputMeter>,
+}
+
+// Gestionnaire principal des téléchargements
+pub struct DownloadManager {
+    downloads: Arc<RwLock<HashMap<String, DownloadProgress>>>,
+    groups: Arc<RwLock<HashMap<String, DownloadGroup>>>,
+    // Configuration courante et client HTTP reconstruit à chaque modification
+    settings: Arc<std::sync::RwLock<Settings>>,
+    // Fichier où la configuration est persistée
+    config_path: Option<PathBuf>,
+    active_tasks: Arc<RwLock<HashMap<String, ActiveTask>>>,
+    journal: Option<Arc<DownloadJournal>>,
+    limiter: Arc<BandwidthLimiter>,
+    // File d'attente ordonnée des téléchargements Pending
+    queue: Arc<RwLock<Vec<String>>>,
+    schedule_lock: Arc<tokio::sync::Mutex<()>>,
+    // Destination des événements de progression, de fin et d'échec
+    events: Arc<dyn DownloadEventSink>,
+    // Cache adressé par contenu des fichiers terminés
+    store: Option<Arc<ContentStore>>,
+    // Plages horaires et démarrage différé de la file
+    schedule: Arc<RwLock<DownloadSchedule>>,
+}
+
+impl DownloadManager {
+    pub fn new(config: DownloadConfig) -> Self {
+        let (config, client) = match build_client(&config) {
+            Ok(client) => (config, client),
+            Err(e) => {
+                // Des réglages réseau invalides ne doivent pas empêcher le démarrage
+                warn!("Invalid network configuration, using defaults: {}", e);
+                let config = DownloadConfig {
+                    network: NetworkConfig::default(),
+                    ..config
+                };
+                let client = build_client(&config).expect("Failed to create HTTP client");
+                (config, client)
+            }
+        };
+
+        Self {
+            downloads: Arc::new(RwLock::new(HashMap::new())),
+            groups: Arc::new(RwLock::new(HashMap::new())),
+            limiter: Arc::new(BandwidthLimiter::new(config.max_bytes_per_second)),
+            settings: Arc::new(std::sync::RwLock::new(Settings { config, client })),
+            config_path: None,
+            active_tasks: Arc::new(RwLock::new(HashMap::new())),
+            journal: None,
+            queue: Arc::new(RwLock::new(Vec::new())),
+            schedule_lock: Arc::new(tokio::sync::Mutex::new(())),
+            events: Arc::new(NullEventSink),
+            store: None,
+            schedule: Arc::new(RwLock::new(DownloadSchedule::default())),
+        }
+    }
+
+    // Activer la persistance et recharger les téléchargements du journal
+    pub fn with_journal(mut self, journal_path: PathBuf) -> Self {
+        let journal = DownloadJournal::load(&journal_path);
+
+        // Reprendre l'ordre enregistré de la file ; les téléchargements en attente qui n'y
+        // figurent pas suivent, par priorité décroissante puis ordre d'arrivée
+        let mut pending: Vec<&DownloadProgress> = journal
+            .downloads
+            .iter()
+            .filter(|p| p.status == DownloadStatus::Pending)
+            .collect();
+        let mut queue: Vec<String> = Vec::with_capacity(pending.len());
+        for id in &journal.queue {
+            if !queue.contains(id) && pending.iter().any(|p| &p.id == id) {
+                queue.push(id.clone());
+            }
+        }
+        pending.retain(|p| !queue.contains(&p.id));
+        pending.sort_by_key(|p| {
+            let created_at = chrono::DateTime::parse_from_rfc3339(&p.created_at).ok();
+            (std::cmp::Reverse(p.priority), created_at)
+        });
+        queue.extend(pending.into_iter().map(|p| p.id.clone()));
+        self.queue = Arc::new(RwLock::new(queue));
+
+        self.downloads = Arc::new(RwLock::new(
+            journal.downloads.into_iter().map(|p| (p.id.clone(), p)).collect(),
+        ));
+        self.groups = Arc::new(RwLock::new(
+            journal.groups.into_iter().map(|g| (g.id.clone(), g)).collect(),
+        ));
+        self.schedule = Arc::new(RwLock::new(journal.schedule));
+        self.journal = Some(Arc::new(DownloadJournal::new(journal_path)));
+        self
+    }
+
+    // Charger la configuration persistée (si elle existe) et y enregistrer les modifications
+    pub fn with_config_file(mut self, config_path: PathBuf) -> Self {
+        if let Some(config) = read_config_file(&config_path) {
+            match config.validate().and_then(|_| build_client(&config)) {
+                Ok(client) => {
+                    self.limiter = Arc::new(BandwidthLimiter::new(config.max_bytes_per_second));
+                    *self.settings.write().unwrap() = Settings { config, client };
+                }
+                Err(e) => warn!("Ignoring download configuration {:?}: {}", config_path, e),
+            }
+        }
+        self.config_path = Some(config_path);
+        self
+    }
+
+    // Conserver les fichiers terminés dans un cache adressé par leur SHA-256
+    pub fn with_content_store(mut self, root: PathBuf) -> Self {
+        self.store = Some(Arc::new(ContentStore::new(root)));
+        self
+    }
+
+    // Brancher la destination des événements (Tauri, canal, ...)
+    pub fn with_event_sink(mut self, events: Arc<dyn DownloadEventSink>) -> Self {
+        self.events = events;
+        self
+    }
+
+    fn client(&self) -> Client {
+        self.settings.read().unwrap().client.clone()
+    }
+
+    fn config(&self) -> DownloadConfig {
+        self.settings.read().unwrap().config.clone()
+    }
+
+    // Configuration effective d'un téléchargement
+    async fn download_config(&self, download_id: &str) -> DownloadConfig {
+        let config = self.config();
+        let downloads = self.downloads.read().await;
+        match downloads.get(download_id) {
+            Some(progress) => progress.overrides.apply(&config),
+            None => config,
+        }
+    }
+
+    async fn persist(&self) {
+        if let Some(journal) = &self.journal {
+            let snapshot: Vec<DownloadProgress> = {
+                let downloads = self.downloads.read().await;
+                downloads.values().cloned().collect()
+            };
+            let groups: Vec<DownloadGroup> = {
+                let groups = self.groups.read().await;
+                groups.values().cloned().collect()
+            };
+            let schedule = self.schedule.read().await.clone();
+            let queue = self.queue.read().await.clone();
+
+            let content = JournalFile {
+                version: JOURNAL_VERSION,
+                downloads: snapshot,
+                groups,
+                schedule,
+                queue,
+            };
+            if let Err(e) = journal.save(content).await {
+                warn!("Failed to write download journal: {}", e);
+            }
+        }
+    }
+
+    // Persister au plus une fois par intervalle pendant le transfert
+    async fn persist_throttled(&self) {
+        if self.journal.as_ref().is_some_and(|journal| journal.is_due()) {
+            self.persist().await;
+        }
+    }
+
+    // Démarrer un nouveau téléchargement
+    pub async fn start_download(&self, request: DownloadRequest) -> Result<String, TransferError> {
+        self.start_download_in_group(request, None).await
+    }
+
+    async fn start_download_in_group(
+        &self,
+        request: DownloadRequest,
+        group_id: Option<String>,
+    ) -> Result<String, TransferError> {
+        let download_id = Uuid::new_v4().to_string();
+        let DownloadRequest {
+            url,
+            file_path,
+            mirrors,
+            headers,
+            expected_sha256,
+            priority,
+            start_at,
+            overrides,
+        } = request;
+        overrides.validate()?;
+        let request_headers = parse_headers(&headers)?;
+        let start_at = start_at
+            .map(|start_at| download_schedule::parse_start_time(&start_at).map(|time| time.to_rfc3339()))
+            .transpose()?;
+        let mirrors: Vec<String> = mirrors.into_iter().filter(|m| *m != url).collect();
+        let expected_sha256 = expected_sha256
+            .map(|hash| hash.trim().to_lowercase())
+            .filter(|hash| !hash.is_empty());
+
+        // Contenu déjà présent dans le cache : aucune requête réseau
+        if let Some(size) = self.materialize_from_cache(expected_sha256.as_deref(), &file_path).await {
+            let now = chrono::Utc::now().to_rfc3339();
+            let progress = DownloadProgress {
+                id: download_id.clone(),
+                url,
+                file_path,
+                total_size: Some(size),
+                downloaded: size,
+                speed: 0,
+                percentage: 100.0,
+                status: DownloadStatus::Completed,
+                error: None,
+                chunks: Vec::new(),
+                created_at: now.clone(),
+                started_at: None,
+                completed_at: Some(now),
+                eta_seconds: None,
+                etag: None,
+                last_modified: None,
+                expected_sha256,
+                failure_reason: None,
+                failed_ranges: Vec::new(),
+                priority,
+                mirrors,
+                validator_url: None,
+                headers,
+                group_id,
+                from_cache: true,
+                start_at: None,
+                overrides,
+                reset_generation: 0,
+            };
+
+            {
+                let mut downloads = self.downloads.write().await;
+                downloads.insert(download_id.clone(), progress.clone());
+            }
+            self.persist().await;
+
+            // La fin d'un groupe est signalée par start_download_group une fois tous les membres créés
+            if progress.group_id.is_none() {
+                self.events.emit(DownloadEvent::Completed(progress));
+            }
+            return Ok(download_id);
+        }
+
+        // Une seule requête HEAD (sur la première source disponible) : taille, plages et validateurs
+        let mut sources = vec![url.clone()];
+        sources.extend(mirrors.iter().cloned());
+        let remote = self
+            .probe_sources(&sources, &request_headers, &overrides.apply(&self.config()))
+            .await?;
+        
+        // Vérifier si le fichier existe déjà et est complet : seul un téléchargement terminé du journal
+        // en atteste, un fichier préalloué ou interrompu ayant déjà la taille finale
+        let file_size = self.get_existing_file_size(&file_path).await;
+        let is_complete = {
+            let downloads = self.downloads.read().await;
+            downloads.values().any(|p| {
+                p.file_path == file_path && p.status == DownloadStatus::Completed && p.total_size == Some(file_size)
+            })
+        };
+        if file_size > 0 && Some(file_size) == remote.total_size && is_complete {
+            return Err("File already exists and is complete".into());
+        }
+
+        // Échouer avant de consommer de la bande passante si le volume est trop petit
+        self.check_disk_space(&file_path, remote.total_size)?;
+
+        // Un fichier partiel n'est repris que si un précédent téléchargement de la même
+        // ressource (mêmes validateurs) l'a produit ; sinon il repart de zéro
+        let existing_size = {
+            let mut downloads = self.downloads.write().await;
+            let previous = downloads
+                .values()
+                .find(|p| {
+                    p.url == url
+                        && p.file_path == file_path
+                        && p.validator_source() == remote.url
+                        && p.status != DownloadStatus::Downloading
+                        && (p.etag.is_some() || p.last_modified.is_some())
+                        && remote.matches(p)
+                })
+                .map(|p| (p.id.clone(), p.downloaded, p.chunks.clone()));
+
+            // Un autre transfert inachevé écrit déjà dans ce fichier
+            let previous_id = previous.as_ref().map(|(id, _, _)| id.as_str());
+            let conflicting = downloads.values().any(|p| {
+                p.file_path == file_path
+                    && Some(p.id.as_str()) != previous_id
+                    && matches!(
+                        p.status,
+                        DownloadStatus::Pending | DownloadStatus::Downloading | DownloadStatus::Paused
+                    )
+            });
+            if conflicting {
+                return Err("Another download is already writing to this file".into());
+            }
+
+            match previous {
+                Some((previous_id, downloaded, chunks)) => {
+                    downloads.remove(&previous_id);
+                    let prefix = if chunks.is_empty() { downloaded } else { contiguous_prefix(&chunks) };
+                    prefix.min(file_size)
+                }
+                None => 0,
+            }
+        };
+
+        // Un fichier non repris est recréé : s'il est lié à un objet du cache, ce dernier
+        // ne doit pas être réécrit en place
+        if existing_size == 0 {
+            let _ = tokio::fs::remove_file(&file_path).await;
+        }
+        let total_size = remote.total_size;
+        
+        // Créer la structure de progression
+        let mut progress = DownloadProgress {
+            id: download_id.clone(),
+            url: url.clone(),
+            file_path: file_path.clone(),
+            total_size,
+            downloaded: existing_size,
+            speed: 0,
+            percentage: 0.0,
+            status: DownloadStatus::Pending,
+            error: None,
+            chunks: Vec::new(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            started_at: None,
+            completed_at: None,
+            eta_seconds: None,
+            etag: remote.etag,
+            last_modified: remote.last_modified,
+            expected_sha256,
+            failure_reason: None,
+            failed_ranges: Vec::new(),
+            priority,
+            mirrors,
+            validator_url: Some(remote.url),
+            headers,
+            group_id,
+            from_cache: false,
+            start_at,
+            overrides,
+            reset_generation: 0,
+        };
+        progress.update_percentage();
+
+        // Ajouter aux téléchargements actifs
+        {
+            let mut downloads = self.downloads.write().await;
+            downloads.insert(download_id.clone(), progress.clone());
+        }
+        self.persist().await;
+
+        // Mettre en file et démarrer si un emplacement est libre
+        self.enqueue(&download_id, priority).await;
+        self.schedule_queue().await;
+
+        Ok(download_id)
+    }
+
+    // Insérer dans la file après les téléchargements de priorité supérieure ou égale
+    async fn enqueue(&self, download_id: &str, priority: i32) {
+        let priorities: HashMap<String, i32> = {
+            let downloads = self.downloads.read().await;
+            downloads.iter().map(|(id, p)| (id.clone(), p.priority)).collect()
+        };
+
+        let mut queue = self.queue.write().await;
+        queue.retain(|id| id != download_id);
+        let position = queue
+            .iter()
+            .position(|id| priorities.get(id).copied().unwrap_or(0) < priority)
+            .unwrap_or(queue.len());
+        queue.insert(position, download_id.to_string());
+    }
+
+    async fn dequeue(&self, download_id: &str) {
+        self.queue.write().await.retain(|id| id != download_id);
+    }
+
+    // Démarrer les téléchargements en file tant que la limite de concurrence le permet.
+    // Future boxée : la tâche lancée rappelle schedule_queue en se terminant.
+    fn schedule_queue(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let _guard = self.schedule_lock.lock().await;
+            let max_active = self.config().max_concurrent_downloads.max(1);
+
+            // Hors plage horaire ou file programmée plus tard : rien ne démarre
+            if !self.schedule.read().await.allows_start(chrono::Local::now()) {
+                return;
+            }
+
+            loop {
+                if self.active_tasks.read().await.len() >= max_active {
+                    return;
+                }
+
+                // Les téléchargements programmés plus tard gardent leur place dans la file
+                let not_due: Vec<String> = {
+                    let now = chrono::Utc::now();
+                    let downloads = self.downloads.read().await;
+                    downloads
+                        .values()
+                        .filter(|p| !download_schedule::is_due(p.start_at.as_deref(), now))
+                        .map(|p| p.id.clone())
+                        .collect()
+                };
+
+                let next = {
+                    let mut queue = self.queue.write().await;
+                    match queue.iter().position(|id| !not_due.contains(id)) {
+                        Some(position) => queue.remove(position),
+                        None => return,
+                    }
+                };
+
+                let is_pending = {
+                    let downloads = self.downloads.read().await;
+                    downloads.get(&next).is_some_and(|p| p.status == DownloadStatus::Pending)
+                };
+                if is_pending {
+                    self.spawn_download(next).await;
+                }
+            }
+        })
+    }
+
+    // Lancer la tâche de téléchargement et l'enregistrer pour pouvoir l'arrêter plus tard
+    async fn spawn_download(&self, download_id: String) {
+        let cancel = CancellationToken::new();
+
+        // Le verrou est conservé jusqu'à l'insertion pour que la tâche ne puisse pas se retirer avant
+        let mut tasks = self.active_tasks.write().await;
+
+        let manager = self.clone();
+        let id = download_id.clone();
+        let token = cancel.clone();
+        let handle = tokio::spawn(async move {
+            manager.execute_download(id, token).await;
+            // Un emplacement s'est libéré
+            manager.schedule_queue().await;
+        });
+
+        let meter = Arc::new(ThroughputMeter::new(Duration::from_secs(THROUGHPUT_WINDOW_SECS)));
+        tasks.insert(download_id, ActiveTask { handle, cancel, meter });
+    }
+
+    // Arrêter la tâche active et attendre qu'elle ait relâché le fichier
+    async fn stop_task(&self, download_id: &str) {
+        let task = {
+            let mut tasks = self.active_tasks.write().await;
+            tasks.remove(download_id)
+        };
+
+        if let Some(task) = task {
+            task.cancel.cancel();
+            let _ = task.handle.await;
+        }
+    }
+
+    // Exécuter le téléchargement avec la stratégie appropriée
+    async fn execute_download(&self, download_id: String, cancel: CancellationToken) {
+        // Mis en pause ou annulé avant même d'avoir démarré
+        if cancel.is_cancelled() {
+            return;
+        }
+
+        // Revalider la ressource (elle a pu changer pendant l'attente en file ou la pause)
+        let mut supports_partial = self.revalidate(&download_id).await;
+
+        // Marquer comme démarré
+        let expected_sha256 = {
+            let mut downloads = self.downloads.write().await;
+            match downloads.get_mut(&download_id) {
+                Some(progress) => {
+                    progress.status = DownloadStatus::Downloading;
+                    progress.started_at = Some(chrono::Utc::now().to_rfc3339());
+                    progress.failed_ranges.clear();
+                    progress.expected_sha256.clone()
+                }
+                None => None,
+            }
+        };
+        self.persist().await;
+
+        // Hacher en parallèle du téléchargement pour éviter une seconde lecture complète ;
+        // l'empreinte sert aussi de clé dans le cache
+        let hash_file = expected_sha256.is_some() || self.store.is_some();
+        let mut hashing = hash_file.then(|| self.spawn_hasher(&download_id));
+
+        let mut restarts = 0;
+        let result = loop {
+            let total_size = {
+                let downloads = self.downloads.read().await;
+                downloads.get(&download_id).and_then(|p| p.total_size)
+            };
+
+            let chunk_size = self.download_config(&download_id).await.chunk_size;
+
+            // L'espace a pu être consommé pendant l'attente en file
+            let file_path = {
+                let downloads = self.downloads.read().await;
+                downloads.get(&download_id).map(|p| p.file_path.clone()).unwrap_or_default()
+            };
+
+            // Une taille inconnue impose un flux mono-thread
+            let result = if let Err(e) = self.check_disk_space(&file_path, total_size) {
+                Err(e)
+            } else {
+                let hasher = hashing.as_ref().map(|task| task.hasher.clone());
+                if supports_partial && total_size.is_some_and(|size| size > chunk_size) {
+                    self.download_with_chunks(download_id.clone(), hasher, &cancel).await
+                } else {
+                    self.download_single_thread(download_id.clone(), hasher, &cancel).await
+                }
+            };
+
+            match result {
+                Err(TransferError::ResourceChanged) if restarts < MAX_RESTARTS && !cancel.is_cancelled() => {
+                    restarts += 1;
+                    info!("Remote file changed for download {}, restarting from zero", download_id);
+
+                    let (sources, headers) = {
+                        let downloads = self.downloads.read().await;
+                        downloads
+                            .get(&download_id)
+                            .map(|p| (p.sources(), p.request_headers()))
+                            .unwrap_or_default()
+                    };
+                    let config = self.download_config(&download_id).await;
+                    match self.probe_sources(&sources, &headers, &config).await {
+                        Ok(remote) => {
+                            // Un serveur qui ignore les plages malgré Accept-Ranges finit en mono-thread
+                            supports_partial = remote.supports_partial && restarts < MAX_RESTARTS;
+
+                            // L'empreinte en cours porte sur l'ancien contenu : repartir d'un hachage neuf
+                            if let Some(task) = hashing.take() {
+                                task.finish().await;
+                            }
+                            if let Err(e) = self.reset_download(&download_id, remote).await {
+                                break Err(e);
+                            }
+                            hashing = hash_file.then(|| self.spawn_hasher(&download_id));
+                        }
+                        Err(e) => break Err(e.into()),
+                    }
+                }
+                result => break result,
+            }
+        };
+
+        let hasher = match hashing {
+            Some(task) => Some(task.finish().await),
+            None => None,
+        };
+
+        // Pause ou annulation : le statut a déjà été fixé par l'appelant
+        if cancel.is_cancelled() {
+            self.persist().await;
+            return;
+        }
+
+        // Terminer le hachage, comparer à l'empreinte attendue et déposer le fichier dans le cache
+        let result = match result {
+            Ok(()) if hash_file => {
+                let hasher = hasher.unwrap_or_else(|| StreamingHasher::new(0));
+                match self.verify_sha256(&download_id, hasher, expected_sha256.as_deref()).await {
+                    Ok(sha256) => {
+                        self.store_completed(&download_id, &sha256).await;
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            result => result,
+        };
+
+        // Nettoyer la tâche
+        {
+            let mut tasks = self.active_tasks.write().await;
+            tasks.remove(&download_id);
+        }
+
+        // Gérer le résultat
+        let group_id = {
+            let downloads = self.downloads.read().await;
+            downloads.get(&download_id).and_then(|p| p.group_id.clone())
+        };
+        match result {
+            Ok(_) => {
+                let mut downloads = self.downloads.write().await;
+                if let Some(progress) = downloads.get_mut(&download_id) {
+                    progress.status = DownloadStatus::Completed;
+                    // Une taille inconnue est connue une fois le flux terminé
+                    let total_size = *progress.total_size.get_or_insert(progress.downloaded);
+                    progress.downloaded = total_size;
+                    progress.percentage = 100.0;
+                    progress.set_throughput(0);
+                    progress.error = None;
+                    progress.completed_at = Some(chrono::Utc::now().to_rfc3339());
+                    
+                    // Un membre de groupe ne signale que la fin du groupe entier
+                    if group_id.is_none() {
+                        self.events.emit(DownloadEvent::Completed(progress.clone()));
+                    }
+                }
+            }
+            Err(e) => {
+                let mut downloads = self.downloads.write().await;
+                if let Some(progress) = downloads.get_mut(&download_id) {
+                    progress.status = DownloadStatus::Failed;
+                    progress.error = Some(e.to_string());
+                    progress.failure_reason = Some(e.reason());
+                    if let TransferError::ChunksFailed(ranges) = &e {
+                        progress.failed_ranges = ranges.clone();
+                    }
+                    progress.set_throughput(0);
+                    
+                    self.events.emit(DownloadEvent::Failed(progress.clone()));
+                }
+            }
+        }
+
+        if let Some(group_id) = group_id {
+            self.check_group_finished(&group_id).await;
+        }
+        self.persist().await;
+    }
+
+    // Lancer le hachage au fil de l'eau et sa tâche de rattrapage
+    fn spawn_hasher(&self, download_id: &str) -> HashingTask {
+        let hasher = Arc::new(tokio::sync::Mutex::new(StreamingHasher::new(0)));
+        let stop = CancellationToken::new();
+        let manager = self.clone();
+        let id = download_id.to_string();
+        let shared = hasher.clone();
+        let stop_clone = stop.clone();
+        let handle = tokio::spawn(async move { manager.hash_while_downloading(id, shared, stop_clone).await });
+        HashingTask { hasher, stop, handle }
+    }
+
+    // Rattraper sur disque le préfixe contigu que les écrivains n'ont pas pu hacher à la volée
+    // (plages terminées hors ordre) ; sans retard, aucun octet n'est relu
+    async fn hash_while_downloading(&self, download_id: String, hasher: SharedHasher, stop: CancellationToken) {
+        // Une erreur qui se répète à chaque passage n'est journalisée que périodiquement
+        let mut last_error_log: Option<Instant> = None;
+
+        loop {
+            let written = {
+                let downloads = self.downloads.read().await;
+                downloads.get(&download_id).map(|p| {
+                    let prefix = if p.chunks.is_empty() { p.downloaded } else { contiguous_prefix(&p.chunks) };
+                    (p.file_path.clone(), prefix, p.reset_generation)
+                })
+            };
+
+            if let Some((file_path, prefix, generation)) = written {
+                let result = hasher.lock().await.advance(&file_path, prefix, generation).await;
+                if let Err(e) = result {
+                    let interval = Duration::from_secs(HASH_ERROR_LOG_INTERVAL_SECS);
+                    if last_error_log.map_or(true, |logged| logged.elapsed() >= interval) {
+                        warn!("Streaming hash error for download {}: {}", download_id, e);
+                        last_error_log = Some(Instant::now());
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = stop.cancelled() => break,
+                _ = sleep(Duration::from_millis(self.config().progress_update_interval_ms)) => {}
+            }
+        }
+    }
+
+    // Renvoie l'empreinte du fichier complet
+    async fn verify_sha256(
+        &self,
+        download_id: &str,
+        mut hasher: StreamingHasher,
+        expected: Option<&str>,
+    ) -> Result<String, TransferError> {
+        let (file_path, total_size, generation) = {
+            let downloads = self.downloads.read().await;
+            let progress = downloads.get(download_id).ok_or("Download not found")?;
+            (
+                progress.file_path.clone(),
+                progress.total_size.unwrap_or(progress.downloaded),
+                progress.reset_generation,
+            )
+        };
+
+        hasher.advance(&file_path, total_size, generation).await?;
+        if hasher.offset != total_size {
+            return Err(format!("Downloaded file is shorter than expected ({} of {} bytes)", hasher.offset, total_size).into());
+        }
+
+        let actual = hasher.finalize();
+        if let Some(expected) = expected.filter(|expected| *expected != actual) {
+            // Un fichier corrompu ne doit pas passer pour un fichier complet au prochain démarrage
+            let _ = tokio::fs::remove_file(&file_path).await;
+            return Err(TransferError::HashMismatch {
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+
+        Ok(actual)
+    }
+
+    // Placer à `file_path` le contenu du cache correspondant à l'empreinte ; renvoie sa taille
+    async fn materialize_from_cache(&self, sha256: Option<&str>, file_path: &str) -> Option<u64> {
+        let store = self.store.clone()?;
+        let sha256 = sha256?.to_string();
+        store.lookup(&sha256)?;
+
+        // Ne pas remplacer un fichier qu'un autre téléchargement est en train d'écrire
+        {
+            let downloads = self.downloads.read().await;
+            if downloads.values().any(|p| {
+                p.file_path == file_path
+                    && matches!(p.status, DownloadStatus::Pending | DownloadStatus::Downloading | DownloadStatus::Paused)
+            }) {
+                return None;
+            }
+        }
+
+        let target = PathBuf::from(file_path);
+        match tokio::task::spawn_blocking(move || store.materialize(&sha256, &target)).await {
+            Ok(Ok(size)) => Some(size),
+            Ok(Err(e)) => {
+                warn!("Failed to reuse cached file for {}: {}", file_path, e);
+                None
+            }
+            Err(e) => {
+                warn!("Failed to reuse cached file for {}: {}", file_path, e);
+                None
+            }
+        }
+    }
+
+    // Déposer un fichier terminé dans le cache ; un échec n'invalide pas le téléchargement
+    async fn store_completed(&self, download_id: &str, sha256: &str) {
+        let Some(store) = self.store.clone() else {
+            return;
+        };
+        let file_path = {
+            let downloads = self.downloads.read().await;
+            match downloads.get(download_id) {
+                Some(progress) => PathBuf::from(&progress.file_path),
+                None => return,
+            }
+        };
+
+        let sha256 = sha256.to_string();
+        match tokio::task::spawn_blocking(move || store.insert(&file_path, &sha256)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("Failed to add download {} to the cache: {}", download_id, e),
+            Err(e) => warn!("Failed to add download {} to the cache: {}", download_id, e),
+        }
+    }
+
+    // Téléchargement avec chunks parallèles
+    async fn download_with_chunks(
+        &self,
+        download_id: String,
+        hasher: Option<SharedHasher>,
+        cancel: &CancellationToken,
+    ) -> Result<(), TransferError> {
+        let (mut target, total_size, mut existing_size, mut saved_chunks) = {
+            let downloads = self.downloads.read().await;
+            let progress = downloads.get(&download_id).ok_or("Download not found")?;
+            (
+                TransferTarget {
+                    hasher,
+                    ..TransferTarget::from_progress(progress, &self.config())
+                },
+                progress.total_size.ok_or("Unknown file size")?,
+                progress.downloaded,
+                progress.chunks.clone(),
+            )
+        };
+
+        // S'assurer que le répertoire parent existe
+        if let Some(parent) = Path::new(&target.file_path).parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+
+        // Le fichier a pu être supprimé ou tronqué depuis la sauvegarde : les plages
+        // marquées terminées dans le journal ne sont alors plus sur disque
+        let expected_len = if saved_chunks.is_empty() { existing_size } else { total_size };
+        let file_len = tokio::fs::metadata(&target.file_path).await.map_or(0, |m| m.len());
+        if file_len < expected_len {
+            warn!(
+                "File of download {} is shorter than its journal ({} of {} bytes), restarting from zero",
+                download_id, file_len, expected_len
+            );
+            {
+                let mut downloads = self.downloads.write().await;
+                if let Some(progress) = downloads.get_mut(&download_id) {
+                    progress.downloaded = 0;
+                    progress.percentage = 0.0;
+                    progress.chunks.clear();
+                    progress.reset_generation += 1;
+                    target.generation = progress.reset_generation;
+                }
+            }
+            // Supprimer plutôt que tronquer : le fichier peut être lié à un objet du cache
+            remove_if_exists(&target.file_path).await?;
+            existing_size = 0;
+            saved_chunks.clear();
+        }
+
+        // Reprendre les chunks sauvegardés ou en planifier de nouveaux
+        let chunks = if saved_chunks.is_empty() {
+            // Ne rien conserver au-delà du préfixe validé
+            let file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(false)
+                .open(&target.file_path)
+                .await
+                .map_err(|e| e.to_string())?;
+            file.set_len(existing_size).await.map_err(|e| e.to_string())?;
+
+            let chunks = self.plan_chunks(&target.config, total_size, existing_size);
+            let mut downloads = self.downloads.write().await;
+            if let Some(progress) = downloads.get_mut(&download_id) {
+                progress.chunks = chunks.clone();
+            }
+            chunks
+        } else {
+            saved_chunks
+        };
+
+        // Réserver la taille finale : les chunks écrivent dans un fichier déjà alloué
+        preallocate(&target.file_path, total_size).await?;
+
+        let next_id = chunks.iter().map(|c| c.id + 1).max().unwrap_or(0);
+        let chunks: Vec<ChunkProgress> = chunks
+            .into_iter()
+            .filter(|c| c.status != ChunkStatus::Completed)
+            .collect();
+        if chunks.is_empty() {
+            return Ok(());
+        }
+
+        let scheduler = Arc::new(ChunkScheduler::new(chunks, next_id, target.config.chunk_size));
+
+        // Jeton enfant : un chunk qui détecte un changement de la ressource arrête les autres
+        let chunk_cancel = cancel.child_token();
+
+        let mut round = 0;
+        loop {
+            self.run_chunk_workers(&download_id, &scheduler, &target, &chunk_cancel).await?;
+            if cancel.is_cancelled() {
+                return Err("Download interrupted".into());
+            }
+
+            let failed = scheduler.take_failed();
+            if failed.is_empty() {
+                return Ok(());
+            }
+
+            // Réessayer uniquement les plages manquantes ; les chunks terminés ne sont pas retouchés
+            if round < FAILED_RANGE_RETRY_ROUNDS {
+                round += 1;
+                info!("Retrying {} failed chunk(s) of download {} (pass {})", failed.len(), download_id, round);
+                scheduler.requeue(
+                    failed
+                        .into_iter()
+                        .map(|(mut chunk, _)| {
+                            chunk.status = ChunkStatus::Pending;
+                            chunk.source_url = None;
+                            chunk
+                        })
+                        .collect(),
+                );
+                continue;
+            }
+
+            let ranges = failed
+                .into_iter()
+                .map(|(chunk, error)| FailedRange {
+                    start: chunk.start + chunk.downloaded,
+                    end: chunk.end,
+                    error,
+                })
+                .collect();
+            return Err(TransferError::ChunksFailed(ranges));
+        }
+    }
+
+    // Faire travailler les workers jusqu'à épuisement de la file, en ajoutant
+    // des connexions tant que le débit global progresse
+    async fn run_chunk_workers(
+        &self,
+        download_id: &str,
+        scheduler: &Arc<ChunkScheduler>,
+        target: &TransferTarget,
+        cancel: &CancellationToken,
+    ) -> Result<(), TransferError> {
+        let meter = self.task_meter(download_id).await;
+
+        let spawn_worker = |workers: &mut tokio::task::JoinSet<Result<(), TransferError>>| {
+            let manager = self.clone();
+            let id = download_id.to_string();
+            let scheduler = scheduler.clone();
+            let target = target.clone();
+            let cancel = cancel.clone();
+            workers.spawn(async move { manager.chunk_worker(id, scheduler, target, cancel).await });
+        };
+
+        let max_workers = target.config.max_concurrent_chunks.max(1);
+        let mut workers = tokio::task::JoinSet::new();
+        let mut worker_count = 0;
+        while worker_count < INITIAL_CHUNK_WORKERS.min(max_workers) {
+            spawn_worker(&mut workers);
+            worker_count += 1;
+        }
+
+        let scale_interval = Duration::from_millis(CHUNK_SCALE_INTERVAL_MS);
+        let mut scale = tokio::time::interval_at(Instant::now() + scale_interval, scale_interval);
+        let mut best_rate = 0;
+
+        let mut has_error = false;
+        let mut resource_changed = false;
+        loop {
+            tokio::select! {
+                joined = workers.join_next() => match joined {
+                    None => break,
+                    Some(Ok(Ok(()))) => {}
+                    Some(Ok(Err(TransferError::ResourceChanged))) => resource_changed = true,
+                    Some(Ok(Err(_))) => {}
+                    Some(Err(e)) => {
+                        warn!("Chunk download error: {:?}", e);
+                        has_error = true;
+                    }
+                },
+                _ = scale.tick(), if worker_count < max_workers => {
+                    let rate = meter.bytes_per_second();
+                    if rate > best_rate + best_rate / 10 && scheduler.has_work() {
+                        best_rate = rate;
+                        spawn_worker(&mut workers);
+                        worker_count += 1;
+                    }
+                }
+            }
+        }
+
+        if resource_changed {
+            return Err(TransferError::ResourceChanged);
+        }
+
+        if has_error {
+            return Err("A chunk worker stopped unexpectedly".into());
+        }
+
+        Ok(())
+    }
+
+    // Worker : télécharge des plages de la file partagée jusqu'à épuisement
+    async fn chunk_worker(
+        &self,
+        download_id: String,
+        scheduler: Arc<ChunkScheduler>,
+        target: TransferTarget,
+        cancel: CancellationToken,
+    ) -> Result<(), TransferError> {
+        while let Some(assignment) = scheduler.next() {
+            // Plage volée : raccourcir le chunk d'origine et exposer le nouveau
+            if let Some(victim_id) = assignment.split_from {
+                let mut downloads = self.downloads.write().await;
+                if let Some(progress) = downloads.get_mut(&download_id) {
+                    if let Some(victim) = progress.chunks.iter_mut().find(|c| c.id == victim_id) {
+                        victim.end = victim.end.min(assignment.chunk.start - 1);
+                    }
+                    progress.chunks.push(assignment.chunk.clone());
+                }
+            }
+
+            let mut chunk = assignment.chunk;
+            match self
+                .download_chunk(download_id.clone(), &mut chunk, &target, &scheduler, cancel.clone())
+                .await
+            {
+                Ok(()) => scheduler.finish(chunk.id),
+                // Changement de la ressource, pause ou annulation : arrêter ce worker
+                Err(e) if matches!(e, TransferError::ResourceChanged) || cancel.is_cancelled() => {
+                    scheduler.finish(chunk.id);
+                    return Err(e);
+                }
+                // Les autres chunks continuent ; celui-ci sera repris au passage suivant
+                Err(e) => scheduler.fail(chunk, e.to_string()),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Découper la partie restante du fichier en chunks
+    fn plan_chunks(&self, config: &DownloadConfig, total_size: u64, existing_size: u64) -> Vec<ChunkProgress> {
+        let mut chunks = Vec::new();
+
+        // La partie déjà présente sur disque est représentée par un chunk terminé
+        if existing_size > 0 {
+            chunks.push(ChunkProgress {
+                id: 0,
+                start: 0,
+                end: existing_size - 1,
+                downloaded: existing_size,
+                status: ChunkStatus::Completed,
+                source_url: None,
+                speed: 0,
+                error: None,
+            });
+        }
+
+        let remaining_size = total_size.saturating_sub(existing_size);
+        if remaining_size == 0 {
+            return chunks;
+        }
+
+        // Des plages petites devant le fichier, mais jamais sous `chunk_size`
+        let max_chunks = config.max_concurrent_chunks.max(1) as u64 * CHUNKS_PER_WORKER;
+        let chunk_size = (remaining_size / max_chunks).max(config.chunk_size).max(1);
+        let chunk_count = remaining_size.div_ceil(chunk_size) as usize;
+
+        for i in 0..chunk_count {
+            let start = existing_size + (i as u64 * chunk_size);
+            let end = if i == chunk_count - 1 {
+                total_size - 1
+            } else {
+                start + chunk_size - 1
+            };
+
+            chunks.push(ChunkProgress {
+                id: chunks.len(),
+                start,
+                end,
+                downloaded: 0,
+                status: ChunkStatus::Pending,
+                source_url: None,
+                speed: 0,
+                error: None,
+            });
+        }
+
+        chunks
+    }
+
+    // Téléchargement d'un chunk spécifique, avec bascule sur le miroir suivant
+    // une fois les tentatives épuisées sur une source
+    async fn download_chunk(
         &self,
         download_id: String,
-        mut chunk: ChunkProgress,
-        url: String,
-        file_path: String,
-        app: AppHandle,
-    ) -> Result<(), String> {
-        let mut retries = 0;
+        chunk: &mut ChunkProgress,
+        target: &TransferTarget,
+        scheduler: &ChunkScheduler,
+        cancel: CancellationToken,
+    ) -> Result<(), TransferError> {
+        let source_count = target.sources.len();
+        let first_source = target
+            .source_index(chunk.source_url.as_deref())
+            .unwrap_or(if target.config.spread_chunks_across_mirrors { chunk.id % source_count } else { 0 });
+        let policy = target.config.retry_policy();
+        let mut attempts = 0;
+        let mut last_error = String::new();
+
+        for offset in 0..source_count {
+            let source = &target.sources[(first_source + offset) % source_count];
+            chunk.source_url = Some(source.url.clone());
+            let mut retries = 0;
+
+            while retries < target.config.max_retries {
+                chunk.status = ChunkStatus::Downloading;
+                self.update_chunk_progress(&download_id, chunk).await;
+
+                let result = self
+                    .download_chunk_attempt(&download_id, chunk, target, source, scheduler, &cancel)
+                    .await;
+                chunk.speed = 0;
+
+                // Inutile de réessayer : tout le fichier doit repartir de zéro
+                if let Err(TransferError::ResourceChanged) = result {
+                    cancel.cancel();
+                    return result;
+                }
+
+                // Mis en pause ou annulé : conserver l'offset exact du chunk pour la reprise
+                if cancel.is_cancelled() {
+                    chunk.status = ChunkStatus::Pending;
+                    self.update_chunk_progress(&download_id, chunk).await;
+                    return Err("Download interrupted".into());
+                }
+
+                match result {
+                    Ok(_) => {
+                        chunk.status = ChunkStatus::Completed;
+                        chunk.error = None;
+                        self.update_chunk_progress(&download_id, chunk).await;
+                        self.clear_retry_error(&download_id).await;
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        retries += 1;
+                        attempts += 1;
+                        chunk.status = ChunkStatus::Failed;
+                        chunk.error = Some(e.to_string());
+                        last_error = e.to_string();
+
+                        // Erreur définitive : passer directement au miroir suivant
+                        if e.is_permanent() {
+                            break;
+                        }
+
+                        if retries < target.config.max_retries {
+                            let delay = policy.delay(retries, e.retry_after());
+                            self.update_chunk_progress(&download_id, chunk).await;
+                            self.report_retry(
+                                &download_id,
+                                format!(
+                                    "Chunk {} retrying in {}s (attempt {}/{}): {}",
+                                    chunk.id,
+                                    delay.as_secs_f64().ceil(),
+                                    retries + 1,
+                                    target.config.max_retries,
+                                    e
+                                ),
+                            )
+                            .await;
+                            tokio::select! {
+                                _ = cancel.cancelled() => {}
+                                _ = sleep(delay) => {}
+                            }
+                        }
+                    }
+                }
+            }
+
+            if offset + 1 < source_count {
+                info!("Chunk {} failed on {}, switching to the next mirror: {}", chunk.id, source.url, last_error);
+            }
+        }
+
+        self.update_chunk_progress(&download_id, chunk).await;
+        Err(format!("Chunk {} failed after {} attempt(s): {}", chunk.id, attempts, last_error).into())
+    }
+
+    async fn download_chunk_attempt(
+        &self,
+        download_id: &str,
+        chunk: &mut ChunkProgress,
+        target: &TransferTarget,
+        source: &TransferSource,
+        scheduler: &ChunkScheduler,
+        cancel: &CancellationToken,
+    ) -> Result<(), TransferError> {
+        // La fin a pu être raccourcie par un vol de travail depuis la dernière tentative
+        if let Some(end) = scheduler.advance(chunk.id, chunk.start + chunk.downloaded) {
+            chunk.end = end;
+        }
+
+        // Ouvrir le fichier en mode lecture/écriture
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .read(true)
+            .truncate(false)
+            .open(&target.file_path)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Se positionner au début du chunk
+        file.seek(SeekFrom::Start(chunk.start + chunk.downloaded)).await.map_err(|e| e.to_string())?;
+
+        // Calculer la plage à télécharger
+        let range_start = chunk.start + chunk.downloaded;
+        let range_end = chunk.end;
         
-        while retries < self.config.max_retries {
-            // Vérifier si le téléchargement a été annulé
+        if range_start > range_end {
+            return Ok(()); // Chunk déjà complet
+        }
+
+        // Faire la requête avec range
+        let range_header = format!("bytes={}-{}", range_start, range_end);
+        let mut response = target
+            .send(target.request(&self.client(), source, Some(range_header)))
+            .await?;
+
+        source.check_partial_response(&response, target.total_size)?;
+
+        let mut stream_error = None;
+        let chunk_meter = ThroughputMeter::new(Duration::from_secs(THROUGHPUT_WINDOW_SECS));
+        let download_meter = self.task_meter(download_id).await;
+
+        // Le rapport est périodique même sans données reçues, pour qu'un chunk bloqué tombe à 0 B/s
+        let mut report = interval(Duration::from_millis(target.config.progress_update_interval_ms.max(1)));
+        report.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut last_data = Instant::now();
+        loop {
+            let bytes_read = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => break,
+                _ = report.tick() => {
+                    // Connexion ouverte mais muette : la libérer pour une nouvelle tentative
+                    if last_data.elapsed() >= target.read_timeout() {
+                        stream_error = Some(idle_timeout_error(target.read_timeout()).to_string());
+                        break;
+                    }
+
+                    // Ne journaliser que des octets réellement écrits sur disque
+                    file.flush().await.map_err(|e| e.to_string())?;
+                    chunk.speed = chunk_meter.bytes_per_second();
+                    self.update_chunk_progress(download_id, chunk).await;
+                    self.emit_progress_event(download_id).await;
+                    continue;
+                }
+                bytes_read = response.chunk() => bytes_read,
+            };
+            let bytes_read = match bytes_read {
+                Ok(bytes_read) => bytes_read,
+                Err(e) => {
+                    stream_error = Some(e.to_string());
+                    break;
+                }
+            };
+
+            if let Some(chunk_data) = bytes_read {
+                if chunk_data.is_empty() {
+                    break;
+                }
+
+                // Ne jamais écrire au-delà de la fin courante : la suite a pu être confiée à un autre worker
+                let offset = chunk.start + chunk.downloaded;
+                if let Some(end) = scheduler.advance(chunk.id, offset) {
+                    chunk.end = end;
+                }
+                let writable = (chunk.end + 1).saturating_sub(offset).min(chunk_data.len() as u64) as usize;
+                let data = &chunk_data[..writable];
+
+                file.write_all(data).await.map_err(|e| e.to_string())?;
+                target.hash_written(&mut file, chunk.start, offset, data, target.generation).await;
+                last_data = Instant::now();
+                chunk.downloaded += data.len() as u64;
+                chunk_meter.record(data.len() as u64);
+                download_meter.record(data.len() as u64);
+
+                if chunk.start + chunk.downloaded > chunk.end {
+                    break;
+                }
+
+                // Respecter la limite de bande passante globale
+                tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => break,
+                    _ = self.limiter.acquire(data.len() as u64) => {}
+                }
+            } else {
+                break;
+            }
+        }
+
+        file.flush().await.map_err(|e| e.to_string())?;
+
+        let chunk_size = chunk.end - chunk.start + 1;
+        if chunk.downloaded < chunk_size && !cancel.is_cancelled() {
+            if let Some(e) = stream_error {
+                return Err(e.into());
+            }
+            return Err(format!("Connection closed at {} of {} bytes", chunk.downloaded, chunk_size).into());
+        }
+
+        Ok(())
+    }
+
+    // Téléchargement simple (sans chunks)
+    async fn download_single_thread(
+        &self,
+        download_id: String,
+        hasher: Option<SharedHasher>,
+        cancel: &CancellationToken,
+    ) -> Result<(), TransferError> {
+        let (target, existing_size) = {
+            let mut downloads = self.downloads.write().await;
+            let progress = downloads.get_mut(&download_id).ok_or("Download not found")?;
+
+            // Des chunks sauvegardés ne garantissent qu'un préfixe contigu du fichier
+            if !progress.chunks.is_empty() {
+                progress.downloaded = contiguous_prefix(&progress.chunks);
+                progress.chunks.clear();
+            }
+
+            let target = TransferTarget {
+                hasher,
+                ..TransferTarget::from_progress(progress, &self.config())
+            };
+            (target, progress.downloaded)
+        };
+
+        // S'assurer que le répertoire parent existe
+        if let Some(parent) = Path::new(&target.file_path).parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+
+        let policy = target.config.retry_policy();
+        let mut attempts = 0;
+        let mut last_error = String::new();
+        for source in &target.sources {
+            let mut retries = 0;
+            while retries < target.config.max_retries {
+                let resume_from = {
+                    let downloads = self.downloads.read().await;
+                    downloads.get(&download_id).map(|p| p.downloaded).unwrap_or(existing_size)
+                };
+
+                let result = self
+                    .download_single_attempt(&download_id, &target, source, resume_from, cancel)
+                    .await;
+
+                if cancel.is_cancelled() {
+                    return Err("Download interrupted".into());
+                }
+
+                match result {
+                    Ok(_) => return Ok(()),
+                    Err(TransferError::ResourceChanged) => return Err(TransferError::ResourceChanged),
+                    Err(e) => {
+                        retries += 1;
+                        attempts += 1;
+                        last_error = e.to_string();
+                        if e.is_permanent() {
+                            break;
+                        }
+
+                        if retries < target.config.max_retries {
+                            let delay = policy.delay(retries, e.retry_after());
+                            self.report_retry(
+                                &download_id,
+                                format!(
+                                    "Retrying in {}s (attempt {}/{}): {}",
+                                    delay.as_secs_f64().ceil(),
+                                    retries + 1,
+                                    target.config.max_retries,
+                                    e
+                                ),
+                            )
+                            .await;
+                            tokio::select! {
+                                _ = cancel.cancelled() => {}
+                                _ = sleep(delay) => {}
+                            }
+                        }
+                    }
+                }
+            }
+
+            warn!("Download {} failed on {}: {}", download_id, source.url, last_error);
+        }
+
+        Err(format!("Download failed after {} attempt(s): {}", attempts, last_error).into())
+    }
+
+    async fn download_single_attempt(
+        &self,
+        download_id: &str,
+        target: &TransferTarget,
+        source: &TransferSource,
+        resume_from: u64,
+        cancel: &CancellationToken,
+    ) -> Result<(), TransferError> {
+        // Ajouter header Range (et If-Range) si reprise
+        let range = (resume_from > 0).then(|| format!("bytes={}-", resume_from));
+        let mut response = target.send(target.request(&self.client(), source, range)).await?;
+
+        if resume_from > 0 {
+            // Reprise refusée : la ressource a changé (ou les plages ne sont pas supportées)
+            source.check_partial_response(&response, target.total_size)?;
+        } else if !response.status().is_success() {
+            return Err(TransferError::from_response(&response));
+        }
+
+        // Ouvrir/créer le fichier (une tentative précédente a pu le recréer depuis la création de `target`)
+        let mut generation = {
+            let downloads = self.downloads.read().await;
+            downloads.get(download_id).map_or(target.generation, |p| p.reset_generation)
+        };
+        let mut file = if resume_from > 0 {
+            // Le fichier peut contenir plus d'octets que le journal : on repart du dernier offset connu
+            let mut file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(false)
+                .open(&target.file_path)
+                .await
+                .map_err(|e| e.to_string())?;
+            file.set_len(resume_from).await.map_err(|e| e.to_string())?;
+            file.seek(SeekFrom::Start(resume_from)).await.map_err(|e| e.to_string())?;
+            file
+        } else {
             {
-                let downloads = self.downloads.read().await;
-                if let Some(progress) = downloads.get(&download_id) {
-                    if progress.status == DownloadStatus::Cancelled {
-                        return Err("Download cancelled".to_string());
+                let mut downloads = self.downloads.write().await;
+                if let Some(progress) = downloads.get_mut(download_id) {
+                    progress.reset_generation += 1;
+                    generation = progress.reset_generation;
+                }
+            }
+            // Recréer plutôt que tronquer : le fichier peut être lié à un objet du cache
+            remove_if_exists(&target.file_path).await?;
+            File::create(&target.file_path).await.map_err(|e| e.to_string())?
+        };
+
+        let mut downloaded = resume_from;
+        let mut stream_error = None;
+        let meter = self.task_meter(download_id).await;
+
+        // Le rapport est périodique même sans données reçues, pour que le débit retombe à 0
+        let mut report = interval(Duration::from_millis(target.config.progress_update_interval_ms.max(1)));
+        report.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut last_data = Instant::now();
+        loop {
+            let chunk_result = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => break,
+                _ = report.tick() => {
+                    if last_data.elapsed() >= target.read_timeout() {
+                        stream_error = Some(idle_timeout_error(target.read_timeout()).to_string());
+                        break;
+                    }
+
+                    file.flush().await.map_err(|e| e.to_string())?;
+                    {
+                        let mut downloads = self.downloads.write().await;
+                        if let Some(progress) = downloads.get_mut(download_id) {
+                            progress.downloaded = downloaded;
+                            progress.update_percentage();
+                            progress.set_throughput(meter.bytes_per_second());
+                        }
+                    }
+
+                    self.emit_progress_event(download_id).await;
+                    continue;
+                }
+                chunk_result = response.chunk() => chunk_result,
+            };
+            // Sans taille connue, seule l'erreur du flux distingue une coupure de la fin du fichier
+            let chunk_result = match chunk_result {
+                Ok(chunk_result) => chunk_result,
+                Err(e) => {
+                    stream_error = Some(e.to_string());
+                    break;
+                }
+            };
+
+            if let Some(chunk) = chunk_result {
+                if chunk.is_empty() {
+                    break;
+                }
+
+                file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+                target.hash_written(&mut file, 0, downloaded, &chunk, generation).await;
+                last_data = Instant::now();
+                downloaded += chunk.len() as u64;
+                meter.record(chunk.len() as u64);
+
+                // Respecter la limite de bande passante globale
+                tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => break,
+                    _ = self.limiter.acquire(chunk.len() as u64) => {}
+                }
+            } else {
+                break;
+            }
+        }
+
+        file.flush().await.map_err(|e| e.to_string())?;
+
+        // Enregistrer l'offset exact atteint (fin, pause ou annulation)
+        {
+            let mut downloads = self.downloads.write().await;
+            if let Some(progress) = downloads.get_mut(download_id) {
+                progress.downloaded = downloaded;
+            }
+        }
+
+        if let Some(e) = stream_error {
+            return Err(e.into());
+        }
+        if let Some(total_size) = target.total_size {
+            if downloaded < total_size && !cancel.is_cancelled() {
+                return Err(format!("Connection closed at {} of {} bytes", downloaded, total_size).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    // Fonctions utilitaires
+
+    // Interroger les sources dans l'ordre et garder la première qui répond ; les headers
+    // ne sont envoyés qu'aux sources de la même origine que la première (l'URL principale)
+    async fn probe_sources(
+        &self,
+        urls: &[String],
+        headers: &HeaderMap,
+        config: &DownloadConfig,
+    ) -> Result<RemoteInfo, String> {
+        let mut last_error = "No download source".to_string();
+        for url in urls {
+            let headers = scoped_headers(&urls[0], url, headers);
+            match self.probe_remote(url, &headers, config).await {
+                Ok(remote) => return Ok(remote),
+                Err(e) => last_error = format!("{}: {}", url, e),
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn probe_remote(&self, url: &str, headers: &HeaderMap, config: &DownloadConfig) -> Result<RemoteInfo, String> {
+        // HEAD d'abord ; certains serveurs ne le supportent pas ou omettent Content-Length
+        let head = with_total_timeout(self.client().head(url).headers(headers.clone()), config);
+        if let Ok(response) = send_within(head, config).await {
+            if response.status().is_success() {
+                let remote = Self::remote_info(url, &response);
+                if remote.total_size.is_some() {
+                    return Ok(remote);
+                }
+            }
+        }
+
+        self.probe_remote_with_get(url, headers, config).await
+    }
+
+    // Sonde GET sur le premier octet : un 206 donne la taille via Content-Range,
+    // un 200 sans Content-Length laisse la taille inconnue. Le corps n'est pas lu.
+    async fn probe_remote_with_get(&self, url: &str, headers: &HeaderMap, config: &DownloadConfig) -> Result<RemoteInfo, String> {
+        let request = self.client().get(url).headers(headers.clone()).header(RANGE, "bytes=0-0");
+        let response = send_within(with_total_timeout(request, config), config)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let mut remote = Self::remote_info(url, &response);
+        if response.status() == StatusCode::PARTIAL_CONTENT {
+            remote.supports_partial = true;
+            remote.total_size = content_range_total(&response);
+        } else {
+            remote.supports_partial = false;
+        }
+
+        Ok(remote)
+    }
+
+    fn remote_info(url: &str, response: &reqwest::Response) -> RemoteInfo {
+        let headers = response.headers();
+        let header = |name: reqwest::header::HeaderName| {
+            headers.get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string())
+        };
+
+        RemoteInfo {
+            url: url.to_string(),
+            total_size: header(CONTENT_LENGTH).and_then(|v| v.parse().ok()),
+            supports_partial: header(ACCEPT_RANGES).map(|v| v == "bytes").unwrap_or(false),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    // Vérifier avant une reprise que la ressource n'a pas changé ; renvoie le support des plages
+    async fn revalidate(&self, download_id: &str) -> bool {
+        let progress = match self.get_download_progress(download_id).await {
+            Some(progress) => progress,
+            None => return false,
+        };
+
+        let config = progress.overrides.apply(&self.config());
+        match self.probe_sources(&progress.sources(), &progress.request_headers(), &config).await {
+            Ok(remote) => {
+                let supports_partial = remote.supports_partial;
+                if !remote.matches(&progress) {
+                    info!("Remote file changed for download {}, restarting from zero", download_id);
+                    if let Err(e) = self.reset_download(download_id, remote).await {
+                        warn!("Failed to reset download {}: {}", download_id, e);
                     }
                 }
+                supports_partial
+            }
+            // Sans HEAD, la reprise mono-thread reste protégée par If-Range
+            Err(_) => false,
+        }
+    }
+
+    // Repartir de zéro avec les nouvelles informations de la ressource
+    async fn reset_download(&self, download_id: &str, remote: RemoteInfo) -> Result<(), TransferError> {
+        let file_path = {
+            let mut downloads = self.downloads.write().await;
+            let progress = downloads.get_mut(download_id).ok_or("Download not found")?;
+            progress.total_size = remote.total_size;
+            progress.etag = remote.etag;
+            progress.last_modified = remote.last_modified;
+            progress.validator_url = Some(remote.url);
+            progress.downloaded = 0;
+            progress.percentage = 0.0;
+            progress.chunks.clear();
+            progress.reset_generation += 1;
+            progress.file_path.clone()
+        };
+
+        // Supprimer plutôt que tronquer : le fichier peut être lié à un objet du cache
+        remove_if_exists(&file_path).await?;
+
+        self.persist().await;
+        Ok(())
+    }
+
+    // Vérifier que le volume cible peut recevoir la suite du fichier et la marge d'extraction
+    fn check_disk_space(&self, file_path: &str, total_size: Option<u64>) -> Result<(), TransferError> {
+        // Les octets déjà présents (ou préalloués) sont comptés comme réservés
+        let existing_size = std::fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+        let required = total_size.unwrap_or(0).saturating_sub(existing_size) + self.config().disk_space_margin_bytes;
+        if required == 0 {
+            return Ok(());
+        }
+
+        let available = available_space(file_path).map_err(|e| format!("Unable to check disk space: {}", e))?;
+        if available < required {
+            return Err(TransferError::InsufficientSpace { required, available });
+        }
+
+        Ok(())
+    }
+
+    async fn get_existing_file_size(&self, file_path: &str) -> u64 {
+        tokio::fs::metadata(file_path)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0)
+    }
+
+    // Compteur de débit de la tâche en cours (un compteur isolé si la tâche a déjà été retirée)
+    async fn task_meter(&self, download_id: &str) -> Arc<ThroughputMeter> {
+        let tasks = self.active_tasks.read().await;
+        tasks
+            .get(download_id)
+            .map(|task| task.meter.clone())
+            .unwrap_or_else(|| Arc::new(ThroughputMeter::new(Duration::from_secs(THROUGHPUT_WINDOW_SECS))))
+    }
+
+    async fn update_chunk_progress(&self, download_id: &str, chunk: &ChunkProgress) {
+        let speed = self.task_meter(download_id).await.bytes_per_second();
+        let mut downloads = self.downloads.write().await;
+        if let Some(progress) = downloads.get_mut(download_id) {
+            if let Some(existing_chunk) = progress.chunks.iter_mut().find(|c| c.id == chunk.id) {
+                // Une fin raccourcie par un vol de travail ne doit pas être rallongée par une copie périmée
+                let end = existing_chunk.end.min(chunk.end);
+                *existing_chunk = chunk.clone();
+                existing_chunk.end = end;
             }
 
-            chunk.status = ChunkStatus::Downloading;
-            self.update_chunk_progress(&download_id, &chunk).await;
+            // Recalculer la progression totale
+            progress.downloaded = progress.chunks.iter().map(|c| c.downloaded).sum();
+            progress.update_percentage();
+            // Une pause ou une annulation a déjà remis le débit à zéro
+            if progress.status == DownloadStatus::Downloading {
+                progress.set_throughput(speed);
+            }
+        }
+    }
 
-            match self.download_chunk_attempt(&download_id, &mut chunk, &url, &file_path, &app).await {
-                Ok(_) => {
-                    chunk.status = ChunkStatus::Completed;
-                    self.update_chunk_progress(&download_id, &chunk).await;
-                    return Ok(());
-                }
-                Err(e) => {
-                    retries += 1;
-                    chunk.status = ChunkStatus::Failed;
-                    
-                    if retries < self.config.max_retries {
-                        sleep(Duration::from_millis(self.config.retry_delay_ms * retries as u64)).await;
-                    } else {
-                        return Err(format!("Chunk {} failed after {} retries: {}", chunk.id, self.config.max_retries, e));
-                    }
-                }
+    // Expliquer dans `error` pourquoi le téléchargement patiente avant de réessayer
+    async fn report_retry(&self, download_id: &str, message: String) {
+        warn!("Download {}: {}", download_id, message);
+        {
+            let mut downloads = self.downloads.write().await;
+            if let Some(progress) = downloads.get_mut(download_id) {
+                progress.error = Some(message);
             }
         }
-        
-        Ok(())
+        self.emit_progress_event(download_id).await;
     }
 
-    async fn download_chunk_attempt(
-        &self,
-        download_id: &str,
-        chunk: &mut ChunkProgress,
-        url: &str,
-        file_path: &str,
-        app: &AppHandle,
-    ) -> Result<(), String> {
-        // Ouvrir le fichier en mode lecture/écriture
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .read(true)
-            .open(file_path)
-            .await
-            .map_err(|e| e.to_string())?;
+    // Effacer le message de nouvelle tentative une fois qu'aucun chunk n'est plus en erreur
+    async fn clear_retry_error(&self, download_id: &str) {
+        let mut downloads = self.downloads.write().await;
+        if let Some(progress) = downloads.get_mut(download_id) {
+            if progress.status == DownloadStatus::Downloading && progress.chunks.iter().all(|c| c.error.is_none()) {
+                progress.error = None;
+            }
+        }
+    }
 
-        // Se positionner au début du chunk
-        file.seek(SeekFrom::Start(chunk.start + chunk.downloaded)).await.map_err(|e| e.to_string())?;
+    async fn emit_progress_event(&self, download_id: &str) {
+        let progress_clone = {
+            let downloads = self.downloads.read().await;
+            downloads.get(download_id).cloned()
+        };
 
-        // Calculer la plage à télécharger
-        let range_start = chunk.start + chunk.downloaded;
-        let range_end = chunk.end;
-        
-        if range_start > range_end {
-            return Ok(()); // Chunk déjà complet
+        if let Some(progress) = progress_clone {
+            let group_id = progress.group_id.clone();
+            self.events.emit(DownloadEvent::Progress(progress));
+
+            if let Some(group) = match group_id {
+                Some(group_id) => self.get_download_group(&group_id).await,
+                None => None,
+            } {
+                self.events.emit(DownloadEvent::GroupProgress(group));
+            }
         }
 
-        // Faire la requête avec range
-        let range_header = format!("bytes={}-{}", range_start, range_end);
-        let mut response = self.client
-            .get(url)
-            .header(RANGE, range_header)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+        self.persist_throttled().await;
+    }
 
-        if !response.status().is_success() && response.status().as_u16() != 206 {
-            return Err(format!("HTTP error: {}", response.status()));
+    // Méthodes publiques pour le contrôle des téléchargements
+    pub async fn pause_download(&self, download_id: &str) -> Result<(), String> {
+        {
+            let mut downloads = self.downloads.write().await;
+            if let Some(progress) = downloads.get_mut(download_id) {
+                if matches!(progress.status, DownloadStatus::Downloading | DownloadStatus::Pending) {
+                    progress.status = DownloadStatus::Paused;
+                    progress.set_throughput(0);
+                } else {
+                    return Err("Download is not in progress".to_string());
+                }
+            } else {
+                return Err("Download not found".to_string());
+            }
         }
 
-        let _buffer = vec![0u8; 8192]; // Buffer de 8KB
-        let _start_time = Instant::now();
-        let mut last_update = Instant::now();
+        // Arrêter réellement les transferts avant de rendre la main
+        self.dequeue(download_id).await;
+        self.stop_task(download_id).await;
 
-        while let Ok(bytes_read) = response.chunk().await {
-            if let Some(chunk_data) = bytes_read {
-                if chunk_data.is_empty() {
-                    break;
-                }
+        self.persist().await;
+        self.schedule_queue().await;
+        Ok(())
+    }
+
+    pub async fn resume_download(&self, download_id: String) -> Result<(), String> {
+        {
+            let downloads = self.downloads.read().await;
+            match downloads.get(&download_id) {
+                Some(progress) if progress.status == DownloadStatus::Paused => {}
+                Some(_) => return Err("Download is not paused".to_string()),
+                None => return Err("Download not found".to_string()),
+            }
+        }
 
-                file.write_all(&chunk_data).await.map_err(|e| e.to_string())?;
-                chunk.downloaded += chunk_data.len() as u64;
+        // Une tâche encore en cours d'arrêt écrirait dans le même fichier : attendre qu'elle l'ait relâché
+        self.stop_task(&download_id).await;
 
-                // Vérifier si on dépasse la taille du chunk
-                if chunk.downloaded > (chunk.end - chunk.start + 1) {
-                    chunk.downloaded = chunk.end - chunk.start + 1;
-                    break;
+        // Une reprise repasse par la file d'attente
+        let priority = {
+            let mut downloads = self.downloads.write().await;
+            match downloads.get_mut(&download_id) {
+                Some(progress) if progress.status == DownloadStatus::Paused => {
+                    progress.status = DownloadStatus::Pending;
+                    progress.priority
                 }
+                // Annulé ou supprimé pendant l'arrêt de la tâche
+                _ => return Err("Download is no longer paused".to_string()),
+            }
+        };
 
-                // Mettre à jour la progression périodiquement
-                let now = Instant::now();
-                if now.duration_since(last_update).as_millis() >= self.config.progress_update_interval_ms as u128 {
-                    self.update_chunk_progress(download_id, chunk).await;
-                    self.emit_progress_event(download_id, app).await;
-                    last_update = now;
+        self.enqueue(&download_id, priority).await;
+        self.persist().await;
+        self.schedule_queue().await;
+        Ok(())
+    }
+
+    pub async fn cancel_download(&self, download_id: &str) -> Result<(), String> {
+        // Marquer comme annulé (un fichier terminé est conservé)
+        let partial_file = {
+            let mut downloads = self.downloads.write().await;
+            match downloads.get_mut(download_id) {
+                Some(progress) if progress.status != DownloadStatus::Completed => {
+                    progress.status = DownloadStatus::Cancelled;
+                    progress.set_throughput(0);
+                    Some(progress.file_path.clone())
                 }
-            } else {
-                break;
+                _ => None,
             }
-        }
+        };
 
-        file.flush().await.map_err(|e| e.to_string())?;
+        // Annuler la tâche active
+        self.dequeue(download_id).await;
+        self.stop_task(download_id).await;
+        self.discard_partial_files(partial_file).await;
+
+        self.persist().await;
+        self.schedule_queue().await;
         Ok(())
     }
 
-    // Téléchargement simple (sans chunks)
-    async fn download_single_thread(&self, download_id: String, app: AppHandle) -> Result<(), String> {
-        let (url, file_path, existing_size) = {
-            let downloads = self.downloads.read().await;
-            let progress = downloads.get(&download_id).ok_or("Download not found")?;
-            (progress.url.clone(), progress.file_path.clone(), progress.downloaded)
+    // Supprimer les fichiers de téléchargements inachevés : préalloués, ils ont souvent déjà leur taille finale
+    async fn discard_partial_files(&self, file_paths: impl IntoIterator<Item = String>) {
+        for file_path in file_paths {
+            if let Err(e) = remove_if_exists(&file_path).await {
+                warn!("Failed to remove partial download {}: {}", file_path, e);
+            }
+        }
+    }
+
+    pub async fn get_download_queue(&self) -> Vec<String> {
+        self.queue.read().await.clone()
+    }
+
+    pub async fn set_download_priority(&self, download_id: &str, priority: i32) -> Result<(), String> {
+        let is_queued = {
+            let mut downloads = self.downloads.write().await;
+            let progress = downloads.get_mut(download_id).ok_or("Download not found")?;
+            progress.priority = priority;
+            progress.status == DownloadStatus::Pending
         };
 
-        // S'assurer que le répertoire parent existe
-        if let Some(parent) = Path::new(&file_path).parent() {
-            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        if is_queued {
+            self.enqueue(download_id, priority).await;
         }
 
-        let mut retries = 0;
-        while retries < self.config.max_retries {
-            // Vérifier si le téléchargement a été annulé
-            {
-                let downloads = self.downloads.read().await;
-                if let Some(progress) = downloads.get(&download_id) {
-                    if progress.status == DownloadStatus::Cancelled {
-                        return Err("Download cancelled".to_string());
-                    }
+        self.persist().await;
+        Ok(())
+    }
+
+    // Réordonner la file ; les téléchargements non listés gardent leur ordre, à la suite
+    pub async fn reorder_download_queue(&self, download_ids: Vec<String>) -> Result<(), String> {
+        {
+            let mut queue = self.queue.write().await;
+
+            if let Some(unknown) = download_ids.iter().find(|id| !queue.contains(id)) {
+                return Err(format!("Download {} is not queued", unknown));
+            }
+
+            let mut reordered = Vec::with_capacity(queue.len());
+            for id in download_ids {
+                if !reordered.contains(&id) {
+                    reordered.push(id);
+                }
+            }
+            for id in queue.iter() {
+                if !reordered.contains(id) {
+                    reordered.push(id.clone());
                 }
             }
 
-            match self.download_single_attempt(&download_id, &url, &file_path, existing_size, &app).await {
-                Ok(_) => return Ok(()),
+            *queue = reordered;
+        }
+
+        self.persist().await;
+        Ok(())
+    }
+
+    pub async fn move_download_to_front(&self, download_id: &str) -> Result<(), String> {
+        {
+            let mut queue = self.queue.write().await;
+            let position = queue
+                .iter()
+                .position(|id| id == download_id)
+                .ok_or_else(|| format!("Download {} is not queued", download_id))?;
+
+            let id = queue.remove(position);
+            queue.insert(0, id);
+        }
+
+        self.persist().await;
+        Ok(())
+    }
+
+    pub async fn get_download_progress(&self, download_id: &str) -> Option<DownloadProgress> {
+        let downloads = self.downloads.read().await;
+        downloads.get(download_id).cloned()
+    }
+
+    pub async fn get_all_downloads(&self) -> Vec<DownloadProgress> {
+        let downloads = self.downloads.read().await;
+        downloads.values().cloned().collect()
+    }
+
+    pub async fn remove_download(&self, download_id: &str) -> Result<(), String> {
+        // S'assurer que le téléchargement est arrêté
+        self.cancel_download(download_id).await?;
+
+        // Supprimer de la liste
+        {
+            let mut downloads = self.downloads.write().await;
+            downloads.remove(download_id);
+        }
+
+        self.persist().await;
+        Ok(())
+    }
+
+    pub async fn get_bandwidth_limit(&self) -> Option<u64> {
+        self.limiter.limit().await
+    }
+
+    pub async fn set_bandwidth_limit(&self, bytes_per_second: Option<u64>) -> Result<(), String> {
+        let config = DownloadConfig {
+            max_bytes_per_second: bytes_per_second,
+            ..self.config()
+        };
+        self.set_download_config(config).await
+    }
+
+    pub fn get_network_config(&self) -> NetworkConfig {
+        self.config().network
+    }
+
+    // Remplacer proxy et certificats ; les requêtes suivantes utilisent le nouveau client
+    pub async fn set_network_config(&self, network: NetworkConfig) -> Result<(), String> {
+        let config = DownloadConfig { network, ..self.config() };
+        self.set_download_config(config).await
+    }
+
+    pub fn get_download_config(&self) -> DownloadConfig {
+        self.config()
+    }
+
+    // Remplacer la configuration ; les téléchargements en cours l'appliquent à leurs
+    // prochaines requêtes
+    pub async fn set_download_config(&self, config: DownloadConfig) -> Result<(), String> {
+        config.validate()?;
+        let client = build_client(&config)?;
+
+        if let Some(config_path) = &self.config_path {
+            write_config_file(config_path, &config).await?;
+        }
+
+        self.limiter.set_limit(config.max_bytes_per_second).await;
+        *self.settings.write().unwrap() = Settings { config, client };
+
+        // La limite de téléchargements simultanés a pu augmenter
+        self.schedule_queue().await;
+        Ok(())
+    }
+
+    // Démarrer plusieurs fichiers suivis comme une seule unité
+    pub async fn start_download_group(
+        &self,
+        requests: Vec<DownloadRequest>,
+        name: Option<String>,
+    ) -> Result<String, TransferError> {
+        if requests.is_empty() {
+            return Err("A download group needs at least one file".into());
+        }
+
+        let group_id = Uuid::new_v4().to_string();
+        {
+            let mut groups = self.groups.write().await;
+            groups.insert(
+                group_id.clone(),
+                DownloadGroup {
+                    id: group_id.clone(),
+                    name,
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    completed_at: None,
+                    failure_reported: false,
+                },
+            );
+        }
+
+        let mut started = Vec::new();
+        for request in requests {
+            match self.start_download_in_group(request, Some(group_id.clone())).await {
+                Ok(download_id) => started.push(download_id),
                 Err(e) => {
-                    retries += 1;
-                    if retries < self.config.max_retries {
-                        sleep(Duration::from_millis(self.config.retry_delay_ms * retries as u64)).await;
-                    } else {
-                        return Err(format!("Download failed after {} retries: {}", self.config.max_retries, e));
+                    // Tout ou rien : ne pas laisser une release à moitié lancée
+                    for download_id in &started {
+                        let _ = self.remove_download(download_id).await;
                     }
+                    self.groups.write().await.remove(&group_id);
+                    self.persist().await;
+                    return Err(e);
                 }
             }
         }
 
-        Ok(())
+        // Tous les membres ont pu être servis par le cache
+        self.check_group_finished(&group_id).await;
+        self.persist().await;
+
+        Ok(group_id)
     }
 
-    async fn download_single_attempt(
-        &self,
-        download_id: &str,
-        url: &str,
-        file_path: &str,
-        resume_from: u64,
-        app: &AppHandle,
-    ) -> Result<(), String> {
-        let mut request = self.client.get(url);
-        
-        // Ajouter header Range si reprise
-        if resume_from > 0 {
-            request = request.header(RANGE, format!("bytes={}-", resume_from));
+    pub async fn get_download_group(&self, group_id: &str) -> Option<DownloadGroupProgress> {
+        let groups = self.groups.read().await;
+        let group = groups.get(group_id)?;
+
+        let downloads = self.downloads.read().await;
+        let mut members: Vec<&DownloadProgress> = downloads
+            .values()
+            .filter(|p| p.group_id.as_deref() == Some(group_id))
+            .collect();
+        members.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        Some(DownloadGroupProgress::aggregate(group, &members))
+    }
+
+    pub async fn get_all_download_groups(&self) -> Vec<DownloadGroupProgress> {
+        let group_ids: Vec<String> = self.groups.read().await.keys().cloned().collect();
+
+        let mut groups = Vec::new();
+        for group_id in group_ids {
+            if let Some(group) = self.get_download_group(&group_id).await {
+                groups.push(group);
+            }
         }
+        groups
+    }
 
-        let mut response = request.send().await.map_err(|e| e.to_string())?;
+    async fn group_member_ids(&self, group_id: &str) -> Result<Vec<String>, String> {
+        if !self.groups.read().await.contains_key(group_id) {
+            return Err("Download group not found".to_string());
+        }
 
-        if !response.status().is_success() && !(resume_from > 0 && response.status().as_u16() == 206) {
-            return Err(format!("HTTP error: {}", response.status()));
+        let downloads = self.downloads.read().await;
+        Ok(downloads
+            .values()
+            .filter(|p| p.group_id.as_deref() == Some(group_id))
+            .map(|p| p.id.clone())
+            .collect())
+    }
+
+    // Signaler une seule fois la réussite ou l'échec du groupe
+    async fn check_group_finished(&self, group_id: &str) {
+        let Some(progress) = self.get_download_group(group_id).await else {
+            return;
+        };
+
+        let event = {
+            let mut groups = self.groups.write().await;
+            let Some(group) = groups.get_mut(group_id) else {
+                return;
+            };
+
+            match progress.status {
+                DownloadStatus::Completed if group.completed_at.is_none() => {
+                    let completed_at = chrono::Utc::now().to_rfc3339();
+                    group.completed_at = Some(completed_at.clone());
+                    Some(DownloadEvent::GroupCompleted(DownloadGroupProgress {
+                        completed_at: Some(completed_at),
+                        ..progress
+                    }))
+                }
+                DownloadStatus::Failed if !group.failure_reported => {
+                    group.failure_reported = true;
+                    Some(DownloadEvent::GroupFailed(progress))
+                }
+                _ => None,
+            }
+        };
+
+        if let Some(event) = event {
+            self.events.emit(event);
         }
+    }
 
-        // Ouvrir/créer le fichier
-        let mut file = if resume_from > 0 {
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(file_path)
-                .await
-                .map_err(|e| e.to_string())?
-        } else {
-            File::create(file_path).await.map_err(|e| e.to_string())?
+    // Mettre tout le groupe en pause. Les statuts changent sous le verrou de l'ordonnanceur
+    // pour qu'aucun membre en file ne démarre entre deux.
+    pub async fn pause_download_group(&self, group_id: &str) -> Result<(), String> {
+        let members = self.group_member_ids(group_id).await?;
+
+        let paused: Vec<String> = {
+            let _guard = self.schedule_lock.lock().await;
+            let mut paused = Vec::new();
+            {
+                let mut downloads = self.downloads.write().await;
+                for download_id in &members {
+                    if let Some(progress) = downloads.get_mut(download_id) {
+                        if matches!(progress.status, DownloadStatus::Downloading | DownloadStatus::Pending) {
+                            progress.status = DownloadStatus::Paused;
+                            progress.set_throughput(0);
+                            paused.push(download_id.clone());
+                        }
+                    }
+                }
+            }
+            self.queue.write().await.retain(|id| !paused.contains(id));
+            paused
         };
 
-        let mut downloaded = resume_from;
-        let start_time = Instant::now();
-        let mut last_update = Instant::now();
+        for download_id in &paused {
+            self.stop_task(download_id).await;
+        }
 
-        while let Ok(chunk_result) = response.chunk().await {
-            if let Some(chunk) = chunk_result {
-                if chunk.is_empty() {
-                    break;
+        self.persist().await;
+        self.schedule_queue().await;
+        Ok(())
+    }
+
+    pub async fn resume_download_group(&self, group_id: &str) -> Result<(), String> {
+        let members = self.group_member_ids(group_id).await?;
+
+        // Une tâche encore en cours d'arrêt écrirait dans le même fichier : attendre qu'elle l'ait relâché
+        let paused: Vec<String> = {
+            let downloads = self.downloads.read().await;
+            members
+                .iter()
+                .filter(|id| downloads.get(*id).is_some_and(|p| p.status == DownloadStatus::Paused))
+                .cloned()
+                .collect()
+        };
+        for download_id in &paused {
+            self.stop_task(download_id).await;
+        }
+
+        {
+            let _guard = self.schedule_lock.lock().await;
+            let mut resumed = Vec::new();
+            {
+                let mut downloads = self.downloads.write().await;
+                for download_id in &paused {
+                    if let Some(progress) = downloads.get_mut(download_id) {
+                        if progress.status == DownloadStatus::Paused {
+                            progress.status = DownloadStatus::Pending;
+                            resumed.push((download_id.clone(), progress.priority));
+                        }
+                    }
                 }
+            }
 
-                file.write_all(&chunk).await.map_err(|e| e.to_string())?;
-                downloaded += chunk.len() as u64;
+            for (download_id, priority) in &resumed {
+                self.enqueue(download_id, *priority).await;
+            }
+        }
 
-                // Mettre à jour la progression
-                let now = Instant::now();
-                if now.duration_since(last_update).as_millis() >= self.config.progress_update_interval_ms as u128 {
-                    {
-                        let mut downloads = self.downloads.write().await;
-                        if let Some(progress) = downloads.get_mut(download_id) {
-                            progress.downloaded = downloaded;
-                            progress.percentage = if progress.total_size > 0 {
-                                (downloaded as f64 / progress.total_size as f64) * 100.0
-                            } else {
-                                0.0
-                            };
-                            
-                            // Calculer la vitesse
-                            let elapsed = now.duration_since(start_time);
-                            if elapsed.as_secs() > 0 {
-                                progress.speed = ((downloaded - resume_from) as f64 / elapsed.as_secs_f64()) as u64;
-                            }
+        self.persist().await;
+        self.schedule_queue().await;
+        Ok(())
+    }
+
+    pub async fn cancel_download_group(&self, group_id: &str) -> Result<(), String> {
+        let members = self.group_member_ids(group_id).await?;
+
+        let (cancelled, partial_files): (Vec<String>, Vec<String>) = {
+            let _guard = self.schedule_lock.lock().await;
+            let mut cancelled = Vec::new();
+            {
+                let mut downloads = self.downloads.write().await;
+                for download_id in &members {
+                    if let Some(progress) = downloads.get_mut(download_id) {
+                        if !matches!(progress.status, DownloadStatus::Completed | DownloadStatus::Failed | DownloadStatus::Cancelled) {
+                            progress.status = DownloadStatus::Cancelled;
+                            progress.set_throughput(0);
+                            cancelled.push((download_id.clone(), progress.file_path.clone()));
                         }
                     }
-
-                    self.emit_progress_event(download_id, app).await;
-                    last_update = now;
                 }
-            } else {
-                break;
             }
+            self.queue.write().await.retain(|id| !cancelled.iter().any(|(cancelled_id, _)| cancelled_id == id));
+            cancelled.into_iter().unzip()
+        };
+
+        for download_id in &cancelled {
+            self.stop_task(download_id).await;
         }
+        self.discard_partial_files(partial_files).await;
 
-        file.flush().await.map_err(|e| e.to_string())?;
+        self.persist().await;
+        self.schedule_queue().await;
         Ok(())
     }
 
-    // Fonctions utilitaires
-    async fn check_partial_support(&self, url: &str) -> Result<bool, String> {
-        let response = self.client
-            .head(url)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+    pub async fn remove_download_group(&self, group_id: &str) -> Result<(), String> {
+        self.cancel_download_group(group_id).await?;
+
+        // Les membres en échec laissent aussi un fichier partiel
+        let partial_files: Vec<String> = {
+            let mut downloads = self.downloads.write().await;
+            let partial_files = downloads
+                .values()
+                .filter(|p| p.group_id.as_deref() == Some(group_id) && p.status == DownloadStatus::Failed)
+                .map(|p| p.file_path.clone())
+                .collect();
+            downloads.retain(|_, p| p.group_id.as_deref() != Some(group_id));
+            partial_files
+        };
+        self.discard_partial_files(partial_files).await;
+        self.groups.write().await.remove(group_id);
 
-        Ok(response.headers().get("accept-ranges")
-            .and_then(|v| v.to_str().ok())
-            .map(|v| v == "bytes")
-            .unwrap_or(false))
+        self.persist().await;
+        Ok(())
     }
 
-    async fn get_file_size(&self, url: &str) -> Result<u64, String> {
-        let response = self.client
-            .head(url)
-            .send()
+    pub async fn list_cache_entries(&self) -> Result<Vec<CacheEntry>, String> {
+        let store = self.store.clone().ok_or("Download cache is disabled")?;
+        tokio::task::spawn_blocking(move || store.list())
             .await
-            .map_err(|e| e.to_string())?;
-
-        response.headers()
-            .get(CONTENT_LENGTH)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|v| v.parse().ok())
-            .ok_or_else(|| "Unable to get file size".to_string())
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())
     }
 
-    async fn get_existing_file_size(&self, file_path: &str) -> u64 {
-        tokio::fs::metadata(file_path)
+    // Nettoyer le cache par ancienneté puis par budget total
+    pub async fn prune_cache(
+        &self,
+        max_age: Option<Duration>,
+        max_total_bytes: Option<u64>,
+    ) -> Result<CachePruneReport, String> {
+        let store = self.store.clone().ok_or("Download cache is disabled")?;
+        tokio::task::spawn_blocking(move || store.prune(max_age, max_total_bytes))
             .await
-            .map(|m| m.len())
-            .unwrap_or(0)
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())
     }
 
-    async fn update_chunk_progress(&self, download_id: &str, chunk: &ChunkProgress) {
-        let mut downloads = self.downloads.write().await;
-        if let Some(progress) = downloads.get_mut(download_id) {
-            if let Some(existing_chunk) = progress.chunks.iter_mut().find(|c| c.id == chunk.id) {
-                *existing_chunk = chunk.clone();
-            }
+    pub async fn get_download_schedule(&self) -> DownloadSchedule {
+        self.schedule.read().await.clone()
+    }
 
-            // Recalculer la progression totale
-            let total_downloaded: u64 = progress.chunks.iter().map(|c| c.downloaded).sum();
-            progress.downloaded = total_downloaded;
-            progress.percentage = if progress.total_size > 0 {
-                (total_downloaded as f64 / progress.total_size as f64) * 100.0
-            } else {
-                0.0
-            };
+    pub async fn set_download_schedule(&self, schedule: DownloadSchedule) -> Result<(), String> {
+        schedule.validate()?;
+        *self.schedule.write().await = schedule;
+
+        self.persist().await;
+        self.apply_schedule().await;
+        Ok(())
+    }
+
+    // Programmer (ou déprogrammer avec None) le démarrage d'un téléchargement en attente
+    pub async fn schedule_download(&self, download_id: &str, start_at: Option<String>) -> Result<(), String> {
+        let start_at = start_at
+            .map(|start_at| download_schedule::parse_start_time(&start_at).map(|time| time.to_rfc3339()))
+            .transpose()?;
+
+        {
+            let mut downloads = self.downloads.write().await;
+            let progress = downloads.get_mut(download_id).ok_or("Download not found")?;
+            if !matches!(progress.status, DownloadStatus::Pending | DownloadStatus::Paused) {
+                return Err("Only pending or paused downloads can be scheduled".to_string());
+            }
+            progress.start_at = start_at;
         }
+
+        self.persist().await;
+        self.schedule_queue().await;
+        Ok(())
     }
 
-    async fn emit_progress_event(&self, download_id: &str, app: &AppHandle) {
-        let progress_clone = {
-            let downloads = self.downloads.read().await;
-            downloads.get(download_id).cloned()
-        };
+    // Démarrer la file restaurée du journal (à appeler une fois l'initialisation terminée)
+    pub async fn start_queued_downloads(&self) {
+        self.schedule_queue().await;
+    }
 
-        if let Some(progress) = progress_clone {
-            let _ = app.emit("download-progress", &progress);
+    // Boucle de fond : applique les plages horaires et démarre les téléchargements programmés
+    pub async fn run_schedule(self) {
+        let mut ticker = interval(Duration::from_secs(SCHEDULE_CHECK_INTERVAL_SECS));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            self.apply_schedule().await;
         }
     }
 
-    // Méthodes publiques pour le contrôle des téléchargements
-    pub async fn pause_download(&self, download_id: &str) -> Result<(), String> {
-        let mut downloads = self.downloads.write().await;
-        if let Some(progress) = downloads.get_mut(download_id) {
-            if progress.status == DownloadStatus::Downloading {
-                progress.status = DownloadStatus::Paused;
-                Ok(())
-            } else {
-                Err("Download is not in progress".to_string())
-            }
-        } else {
-            Err("Download not found".to_string())
+    async fn apply_schedule(&self) {
+        let state = self.schedule.read().await.state_at(chrono::Local::now());
+
+        let cap = match state {
+            ScheduleState::Throttled(bytes_per_second) => Some(bytes_per_second),
+            ScheduleState::Open | ScheduleState::Closed => None,
+        };
+        self.limiter.set_cap(cap).await;
+
+        if state == ScheduleState::Closed {
+            self.suspend_active_downloads().await;
         }
+        self.schedule_queue().await;
     }
 
-    pub async fn resume_download(&self, download_id: String, app: AppHandle) -> Result<(), String> {
-        let should_resume = {
+    // Fin de plage horaire : les téléchargements en cours retournent en file et
+    // reprendront là où ils en étaient à la prochaine plage
+    async fn suspend_active_downloads(&self) {
+        let suspended: Vec<(String, i32)> = {
+            let _guard = self.schedule_lock.lock().await;
+            let active: Vec<String> = self.active_tasks.read().await.keys().cloned().collect();
+
+            let mut suspended = Vec::new();
             let mut downloads = self.downloads.write().await;
-            if let Some(progress) = downloads.get_mut(&download_id) {
-                if progress.status == DownloadStatus::Paused {
-                    progress.status = DownloadStatus::Downloading;
-                    true
-                } else {
-                    false
+            for download_id in &active {
+                if let Some(progress) = downloads.get_mut(download_id) {
+                    if progress.status == DownloadStatus::Downloading {
+                        progress.status = DownloadStatus::Pending;
+                        progress.set_throughput(0);
+                        suspended.push((download_id.clone(), progress.priority));
+                    }
                 }
-            } else {
-                false
             }
+            suspended
         };
 
-        if should_resume {
-            let manager = self.clone();
-            let id = download_id.clone();
-            let task = tokio::spawn(async move {
-                let supports_partial = manager.check_partial_support(
-                    &{
-                        let downloads = manager.downloads.read().await;
-                        downloads.get(&id).map(|p| p.url.clone()).unwrap_or_default()
-                    }
-                ).await.unwrap_or(false);
-                
-                manager.execute_download(id, supports_partial, app).await;
-            });
-
-            let mut tasks = self.active_tasks.write().await;
-            tasks.insert(download_id, task);
+        if suspended.is_empty() {
+            return;
         }
 
-        Ok(())
+        for (download_id, priority) in &suspended {
+            self.stop_task(download_id).await;
+            self.enqueue(download_id, *priority).await;
+            self.emit_progress_event(download_id).await;
+        }
+        self.persist().await;
     }
 
-    pub async fn cancel_download(&self, download_id: &str) -> Result<(), String> {
-        // Marquer comme annulé
+    pub async fn cleanup_completed_downloads(&self) {
         {
             let mut downloads = self.downloads.write().await;
-            if let Some(progress) = downloads.get_mut(download_id) {
-                progress.status = DownloadStatus::Cancelled;
-            }
+            downloads.retain(|_, progress| {
+                !matches!(progress.status, DownloadStatus::Completed | DownloadStatus::Failed | DownloadStatus::Cancelled)
+            });
+
+            // Oublier les groupes dont il ne reste aucun membre
+            let mut groups = self.groups.write().await;
+            groups.retain(|id, _| downloads.values().any(|p| p.group_id.as_deref() == Some(id.as_str())));
         }
 
-        // Annuler la tâche active
-        {
-            let mut tasks = self.active_tasks.write().await;
-            if let Some(handle) = tasks.remove(download_id) {
-                handle.abort();
-            }
+        self.persist().await;
+    }
+}
+
+// Taille du préfixe contigu déjà écrit, à partir de l'octet 0
+fn contiguous_prefix(chunks: &[ChunkProgress]) -> u64 {
+    let mut sorted: Vec<&ChunkProgress> = chunks.iter().collect();
+    sorted.sort_by_key(|c| c.start);
+
+    let mut prefix = 0;
+    for chunk in sorted {
+        if chunk.start != prefix {
+            break;
         }
+        prefix += chunk.downloaded;
+        if chunk.downloaded < chunk.end - chunk.start + 1 {
+            break;
+        }
+    }
+    prefix
+}
 
-        Ok(())
+impl Clone for DownloadManager {
+    fn clone(&self) -> Self {
+        Self {
+            downloads: Arc::clone(&self.downloads),
+            groups: Arc::clone(&self.groups),
+            settings: Arc::clone(&self.settings),
+            config_path: self.config_path.clone(),
+            active_tasks: Arc::clone(&self.active_tasks),
+            journal: self.journal.clone(),
+            limiter: Arc::clone(&self.limiter),
+            queue: Arc::clone(&self.queue),
+            schedule_lock: Arc::clone(&self.schedule_lock),
+            events: Arc::clone(&self.events),
+            store: self.store.clone(),
+            schedule: Arc::clone(&self.schedule),
+        }
     }
+}
 
-    pub async fn get_download_progress(&self, download_id: &str) -> Option<DownloadProgress> {
-        let downloads = self.downloads.read().await;
-        downloads.get(download_id).cloned()
+// Configuration courante et client HTTP qui en découle
+struct Settings {
+    config: DownloadConfig,
+    client: Client,
+}
+
+// Le délai total et le délai d'inactivité s'appliquent par requête, pas au client :
+// un gros fichier lent mais actif ne doit pas expirer
+fn build_client(config: &DownloadConfig) -> Result<Client, String> {
+    let network = &config.network;
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout_seconds))
+        .user_agent("Lysandra-Launcher/1.0");
+
+    if let Some(proxy_url) = network.proxy_url.as_deref().filter(|url| !url.is_empty()) {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| format!("Invalid proxy {}: {}", proxy_url, e))?
+            .no_proxy(reqwest::NoProxy::from_string(&network.no_proxy.join(",")));
+        builder = builder.proxy(proxy);
     }
 
-    pub async fn get_all_downloads(&self) -> Vec<DownloadProgress> {
-        let downloads = self.downloads.read().await;
-        downloads.values().cloned().collect()
+    for path in &network.ca_certificates {
+        let pem = std::fs::read(path).map_err(|e| format!("Failed to read certificate {}: {}", path, e))?;
+        let certificate = reqwest::Certificate::from_pem(&pem)
+            .map_err(|e| format!("Invalid certificate {}: {}", path, e))?;
+        builder = builder.add_root_certificate(certificate);
     }
 
-    pub async fn remove_download(&self, download_id: &str) -> Result<(), String> {
-        // S'assurer que le téléchargement est arrêté
-        self.cancel_download(download_id).await?;
+    builder.build().map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
 
-        // Supprimer de la liste
-        let mut downloads = self.downloads.write().await;
-        downloads.remove(download_id);
+fn read_config_file(path: &Path) -> Option<DownloadConfig> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            warn!("Ignoring unreadable download configuration {:?}: {}", path, e);
+            None
+        }
+    }
+}
 
-        Ok(())
+async fn write_config_file(path: &Path, config: &DownloadConfig) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
     }
 
-    pub async fn cleanup_completed_downloads(&self) {
-        let mut downloads = self.downloads.write().await;
-        downloads.retain(|_, progress| {
-            !matches!(progress.status, DownloadStatus::Completed | DownloadStatus::Failed | DownloadStatus::Cancelled)
-        });
+    // Écriture atomique : fichier temporaire puis renommage
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, content).await.map_err(|e| e.to_string())?;
+    tokio::fs::rename(&tmp_path, path).await.map_err(|e| e.to_string())
+}
+
+// Les identifiants destinés à l'URL principale ne doivent pas fuiter vers un miroir tiers
+fn scoped_headers(primary_url: &str, url: &str, headers: &HeaderMap) -> HeaderMap {
+    let same_origin = match (reqwest::Url::parse(primary_url), reqwest::Url::parse(url)) {
+        (Ok(primary), Ok(url)) => primary.origin() == url.origin(),
+        _ => false,
+    };
+    if same_origin {
+        headers.clone()
+    } else {
+        HeaderMap::new()
     }
 }
 
-impl Clone for DownloadManager {
-    fn clone(&self) -> Self {
-        Self {
-            downloads: Arc::clone(&self.downloads),
-            client: self.client.clone(),
-            config: self.config.clone(),
-            active_tasks: Arc::clone(&self.active_tasks),
+fn parse_headers(headers: &HashMap<String, String>) -> Result<HeaderMap, String> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid header name: {}", name))?;
+        let value = HeaderValue::from_str(value).map_err(|_| format!("Invalid value for header {}", name))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+// Espace libre sur le volume du fichier, dont le dossier peut ne pas encore exister
+fn available_space(file_path: &str) -> std::io::Result<u64> {
+    let mut dir = Path::new(file_path).parent();
+    while let Some(candidate) = dir {
+        if candidate.as_os_str().is_empty() {
+            break;
+        }
+        if candidate.exists() {
+            return fs2::available_space(candidate);
+        }
+        dir = candidate.parent();
+    }
+
+    fs2::available_space(Path::new("."))
+}
+
+// Allouer les blocs du fichier jusqu'à `size` (sans effet s'il est déjà plus grand)
+async fn preallocate(file_path: &str, size: u64) -> Result<(), TransferError> {
+    let path = file_path.to_string();
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)?;
+        fs2::FileExt::allocate(&file, size)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Failed to preallocate {}: {}", file_path, e).into())
+}
+
+// Supprimer le fichier s'il existe (un lien vers le cache n'est ainsi jamais tronqué)
+async fn remove_if_exists(file_path: &str) -> Result<(), TransferError> {
+    match tokio::fs::remove_file(file_path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to remove {}: {}", file_path, e).into())
         }
+        _ => Ok(()),
     }
 }
 
 // Commandes Tauri
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn start_download(
     url: String,
     file_path: String,
+    mirrors: Option<Vec<String>>,
+    headers: Option<HashMap<String, String>>,
+    expected_sha256: Option<String>,
+    priority: Option<i32>,
+    start_at: Option<String>,
+    overrides: Option<DownloadOverrides>,
     manager: State<'_, DownloadManager>,
-    app: AppHandle,
 ) -> Result<String, String> {
-    manager.start_download(url, file_path, app).await
+    let request = DownloadRequest {
+        url,
+        file_path,
+        mirrors: mirrors.unwrap_or_default(),
+        headers: headers.unwrap_or_default(),
+        expected_sha256,
+        priority: priority.unwrap_or(0),
+        start_at,
+        overrides: overrides.unwrap_or_default(),
+    };
+
+    manager.start_download(request).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -754,9 +3315,8 @@ pub async fn pause_download(
 pub async fn resume_download(
     download_id: String,
     manager: State<'_, DownloadManager>,
-    app: AppHandle,
 ) -> Result<(), String> {
-    manager.resume_download(download_id, app).await
+    manager.resume_download(download_id).await
 }
 
 #[tauri::command]
@@ -775,6 +3335,38 @@ pub async fn remove_download(
     manager.remove_download(&download_id).await
 }
 
+#[tauri::command]
+pub async fn get_download_queue(
+    manager: State<'_, DownloadManager>,
+) -> Result<Vec<String>, String> {
+    Ok(manager.get_download_queue().await)
+}
+
+#[tauri::command]
+pub async fn set_download_priority(
+    download_id: String,
+    priority: i32,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.set_download_priority(&download_id, priority).await
+}
+
+#[tauri::command]
+pub async fn reorder_download_queue(
+    download_ids: Vec<String>,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.reorder_download_queue(download_ids).await
+}
+
+#[tauri::command]
+pub async fn move_download_to_front(
+    download_id: String,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.move_download_to_front(&download_id).await
+}
+
 #[tauri::command]
 pub async fn get_download_progress(
     download_id: String,
@@ -798,6 +3390,155 @@ pub async fn cleanup_completed_downloads(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_bandwidth_limit(
+    manager: State<'_, DownloadManager>,
+) -> Result<Option<u64>, String> {
+    Ok(manager.get_bandwidth_limit().await)
+}
+
+#[tauri::command]
+pub async fn set_bandwidth_limit(
+    bytes_per_second: u64,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.set_bandwidth_limit(Some(bytes_per_second)).await
+}
+
+#[tauri::command]
+pub async fn clear_bandwidth_limit(
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.set_bandwidth_limit(None).await
+}
+
+#[tauri::command]
+pub async fn start_download_group(
+    files: Vec<DownloadRequest>,
+    name: Option<String>,
+    manager: State<'_, DownloadManager>,
+) -> Result<String, String> {
+    manager.start_download_group(files, name).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_download_group(
+    group_id: String,
+    manager: State<'_, DownloadManager>,
+) -> Result<Option<DownloadGroupProgress>, String> {
+    Ok(manager.get_download_group(&group_id).await)
+}
+
+#[tauri::command]
+pub async fn get_all_download_groups(
+    manager: State<'_, DownloadManager>,
+) -> Result<Vec<DownloadGroupProgress>, String> {
+    Ok(manager.get_all_download_groups().await)
+}
+
+#[tauri::command]
+pub async fn pause_download_group(
+    group_id: String,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.pause_download_group(&group_id).await
+}
+
+#[tauri::command]
+pub async fn resume_download_group(
+    group_id: String,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.resume_download_group(&group_id).await
+}
+
+#[tauri::command]
+pub async fn cancel_download_group(
+    group_id: String,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.cancel_download_group(&group_id).await
+}
+
+#[tauri::command]
+pub async fn remove_download_group(
+    group_id: String,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.remove_download_group(&group_id).await
+}
+
+#[tauri::command]
+pub async fn list_download_cache(
+    manager: State<'_, DownloadManager>,
+) -> Result<Vec<CacheEntry>, String> {
+    manager.list_cache_entries().await
+}
+
+#[tauri::command]
+pub async fn prune_download_cache(
+    max_age_days: Option<u64>,
+    max_total_bytes: Option<u64>,
+    manager: State<'_, DownloadManager>,
+) -> Result<CachePruneReport, String> {
+    let max_age = max_age_days.map(|days| Duration::from_secs(days * 24 * 60 * 60));
+    manager.prune_cache(max_age, max_total_bytes).await
+}
+
+#[tauri::command]
+pub async fn get_download_schedule(
+    manager: State<'_, DownloadManager>,
+) -> Result<DownloadSchedule, String> {
+    Ok(manager.get_download_schedule().await)
+}
+
+#[tauri::command]
+pub async fn set_download_schedule(
+    schedule: DownloadSchedule,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.set_download_schedule(schedule).await
+}
+
+#[tauri::command]
+pub async fn schedule_download(
+    download_id: String,
+    start_at: Option<String>,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.schedule_download(&download_id, start_at).await
+}
+
+#[tauri::command]
+pub async fn get_network_config(
+    manager: State<'_, DownloadManager>,
+) -> Result<NetworkConfig, String> {
+    Ok(manager.get_network_config())
+}
+
+#[tauri::command]
+pub async fn set_network_config(
+    config: NetworkConfig,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.set_network_config(config).await
+}
+
+#[tauri::command]
+pub async fn get_download_config(
+    manager: State<'_, DownloadManager>,
+) -> Result<DownloadConfig, String> {
+    Ok(manager.get_download_config())
+}
+
+#[tauri::command]
+pub async fn set_download_config(
+    config: DownloadConfig,
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    manager.set_download_config(config).await
+}
+
 #[tauri::command]
 pub async fn get_download_stats(
     manager: State<'_, DownloadManager>,
@@ -806,11 +3547,12 @@ pub async fn get_download_stats(
     
     let stats = DownloadStats {
         total_downloads: downloads.len(),
-        active_downloads: downloads.iter().filter(|d| matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Pending)).count(),
+        active_downloads: downloads.iter().filter(|d| d.status == DownloadStatus::Downloading).count(),
+        queued_downloads: downloads.iter().filter(|d| d.status == DownloadStatus::Pending).count(),
         completed_downloads: downloads.iter().filter(|d| d.status == DownloadStatus::Completed).count(),
         failed_downloads: downloads.iter().filter(|d| d.status == DownloadStatus::Failed).count(),
         total_downloaded_bytes: downloads.iter().map(|d| d.downloaded).sum(),
-        total_size_bytes: downloads.iter().map(|d| d.total_size).sum(),
+        total_size_bytes: downloads.iter().map(|d| d.total_size.unwrap_or(d.downloaded)).sum(),
     };
     
     Ok(stats)
@@ -820,6 +3562,7 @@ pub async fn get_download_stats(
 pub struct DownloadStats {
     pub total_downloads: usize,
     pub active_downloads: usize,
+    pub queued_downloads: usize,
     pub completed_downloads: usize,
     pub failed_downloads: usize,
     pub total_downloaded_bytes: u64,
@@ -827,15 +3570,19 @@ pub struct DownloadStats {
 }
 
 // Fonction d'initialisation pour main.rs
-pub fn init_download_manager() -> DownloadManager {
-    let config = DownloadConfig {
-        max_concurrent_chunks: 8,
-        chunk_size: 2 * 1024 * 1024, // 2MB chunks
-        max_retries: 5,
-        retry_delay_ms: 2000,
-        timeout_seconds: 60,
-        progress_update_interval_ms: 100,
-    };
-    
-    DownloadManager::new(config)
+pub fn init_download_manager(cache_dir: PathBuf, config_dir: PathBuf, app: AppHandle) -> DownloadManager {
+    let manager = DownloadManager::new(DownloadConfig::default())
+        .with_config_file(config_dir.join("download_config.json"))
+        .with_journal(cache_dir.join("downloads.json"))
+        .with_content_store(cache_dir.join("objects"))
+        .with_event_sink(Arc::new(TauriEventSink::new(app)));
+
+    // Relancer la file restaurée, puis appliquer les plages horaires et lancer les téléchargements programmés
+    let restored = manager.clone();
+    tauri::async_runtime::spawn(async move {
+        restored.start_queued_downloads().await;
+        restored.run_schedule().await;
+    });
+
+    manager
 } 
\ No newline at end of file
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Datelike, Local, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Plage horaire récurrente, en heure locale (ex. 01:00–07:00).
+/// Une plage dont la fin précède le début se termine le lendemain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleWindow {
+    pub start: String, // "HH:MM"
+    pub end: String,   // "HH:MM", exclue
+    // Jours où la plage commence ; vide = tous les jours
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+}
+
+/// Comportement des téléchargements en dehors des plages horaires
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum OutsideWindowPolicy {
+    // Les téléchargements en cours retournent en file jusqu'à la prochaine plage
+    #[default]
+    Pause,
+    Throttle { bytes_per_second: u64 },
+}
+
+/// Programmation persistée des téléchargements
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DownloadSchedule {
+    // Aucune plage : les téléchargements tournent à tout moment
+    #[serde(default)]
+    pub windows: Vec<ScheduleWindow>,
+    #[serde(default)]
+    pub outside_windows: OutsideWindowPolicy,
+    // Aucun téléchargement de la file ne démarre avant cette date (RFC 3339)
+    #[serde(default)]
+    pub queue_start_at: Option<String>,
+}
+
+/// État de la programmation à un instant donné
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleState {
+    Open,
+    Throttled(u64),
+    Closed,
+}
+
+impl ScheduleWindow {
+    fn times(&self) -> Result<(NaiveTime, NaiveTime), String> {
+        Ok((parse_time_of_day(&self.start)?, parse_time_of_day(&self.end)?))
+    }
+
+    fn runs_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    fn contains(&self, now: DateTime<Local>) -> bool {
+        let Ok((start, end)) = self.times() else {
+            return false;
+        };
+        let time = now.time();
+        let today = now.weekday();
+
+        if start == end {
+            // Plage de 24 h
+            self.runs_on(today)
+        } else if start < end {
+            self.runs_on(today) && time >= start && time < end
+        } else {
+            (self.runs_on(today) && time >= start) || (self.runs_on(today.pred()) && time < end)
+        }
+    }
+}
+
+impl DownloadSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        for window in &self.windows {
+            window.times()?;
+        }
+        if self.outside_windows == (OutsideWindowPolicy::Throttle { bytes_per_second: 0 }) {
+            return Err("Throttle limit must be greater than zero".to_string());
+        }
+        if let Some(queue_start_at) = &self.queue_start_at {
+            parse_start_time(queue_start_at)?;
+        }
+        Ok(())
+    }
+
+    pub fn state_at(&self, now: DateTime<Local>) -> ScheduleState {
+        if self.windows.is_empty() || self.windows.iter().any(|w| w.contains(now)) {
+            return ScheduleState::Open;
+        }
+
+        match self.outside_windows {
+            OutsideWindowPolicy::Pause => ScheduleState::Closed,
+            OutsideWindowPolicy::Throttle { bytes_per_second } => ScheduleState::Throttled(bytes_per_second),
+        }
+    }
+
+    // La file peut-elle démarrer de nouveaux téléchargements ?
+    pub fn allows_start(&self, now: DateTime<Local>) -> bool {
+        self.state_at(now) != ScheduleState::Closed && is_due(self.queue_start_at.as_deref(), now.with_timezone(&Utc))
+    }
+}
+
+fn parse_time_of_day(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| format!("Invalid time of day: {} (expected HH:MM)", value))
+}
+
+pub fn parse_start_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid start time: {} (expected RFC 3339)", value))
+}
+
+// Une date absente ou illisible ne retient pas le téléchargement
+pub fn is_due(start_at: Option<&str>, now: DateTime<Utc>) -> bool {
+    start_at
+        .and_then(|start_at| parse_start_time(start_at).ok())
+        .map_or(true, |start_at| start_at <= now)
+}
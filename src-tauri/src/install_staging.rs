@@ -0,0 +1,141 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use log::{info, warn};
+
+// Dossiers frères du dossier d'installation :
+// `install.staging` reçoit la nouvelle version, `install.previous` garde l'ancienne
+const STAGING_SUFFIX: &str = "staging";
+const PREVIOUS_SUFFIX: &str = "previous";
+
+fn sibling(install_dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = install_dir.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    install_dir.with_file_name(name)
+}
+
+pub fn staging_dir(install_dir: &Path) -> PathBuf {
+    sibling(install_dir, STAGING_SUFFIX)
+}
+
+pub fn previous_dir(install_dir: &Path) -> PathBuf {
+    sibling(install_dir, PREVIOUS_SUFFIX)
+}
+
+fn is_empty_dir(path: &Path) -> io::Result<bool> {
+    Ok(fs::read_dir(path)?.next().is_none())
+}
+
+// Terminer un échange interrompu (arrêt entre les deux renommages) :
+// sans installation, la version précédente reprend sa place
+pub fn recover(install_dir: &Path) -> io::Result<()> {
+    let previous = previous_dir(install_dir);
+    if !install_dir.exists() && previous.is_dir() {
+        warn!("Restoring {} after an interrupted install swap", install_dir.display());
+        fs::rename(&previous, install_dir)?;
+    }
+    Ok(())
+}
+
+// Créer un dossier de préparation vide, en supprimant les restes d'une extraction avortée
+pub fn prepare_staging(install_dir: &Path) -> Result<PathBuf, String> {
+    recover(install_dir).map_err(|e| format!("Failed to recover previous install: {}", e))?;
+
+    let staging = staging_dir(install_dir);
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|e| format!("Failed to clean staging dir: {}", e))?;
+    }
+    fs::create_dir_all(&staging).map_err(|e| format!("Failed to create staging dir: {}", e))?;
+    Ok(staging)
+}
+
+// Abandonner une préparation qui a échoué ; l'installation en place n'a pas été touchée
+pub fn discard_staging(install_dir: &Path) {
+    let staging = staging_dir(install_dir);
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        if e.kind() != io::ErrorKind::NotFound {
+            warn!("Failed to remove staging dir {}: {}", staging.display(), e);
+        }
+    }
+}
+
+// Mettre la version préparée en place. L'installation actuelle devient le point de retour ;
+// un dossier d'installation vide (première installation) est simplement remplacé.
+pub fn commit_staging(install_dir: &Path) -> Result<(), String> {
+    let staging = staging_dir(install_dir);
+    let previous = previous_dir(install_dir);
+
+    if install_dir.exists() {
+        let empty = is_empty_dir(install_dir).map_err(|e| format!("Failed to read install dir: {}", e))?;
+        if empty {
+            fs::remove_dir(install_dir).map_err(|e| format!("Failed to remove empty install dir: {}", e))?;
+        } else {
+            if previous.exists() {
+                fs::remove_dir_all(&previous).map_err(|e| format!("Failed to remove old rollback point: {}", e))?;
+            }
+            fs::rename(install_dir, &previous).map_err(|e| format!("Failed to move current install aside: {}", e))?;
+        }
+    }
+
+    if let Err(e) = fs::rename(&staging, install_dir) {
+        // Remettre l'ancienne version en place plutôt que de laisser le jeu sans installation
+        let _ = recover(install_dir);
+        return Err(format!("Failed to move new install into place: {}", e));
+    }
+
+    info!("Installed new version into {}", install_dir.display());
+    Ok(())
+}
+
+// Revenir à l'installation précédente ; la version abandonnée est supprimée
+pub fn rollback(install_dir: &Path) -> Result<(), String> {
+    recover(install_dir).map_err(|e| format!("Failed to recover previous install: {}", e))?;
+
+    let previous = previous_dir(install_dir);
+    if !previous.is_dir() {
+        return Err(format!("No previous install to roll back to for {}", install_dir.display()));
+    }
+
+    // Écarter la version actuelle sous le nom du dossier de préparation, puis l'effacer
+    // une fois l'ancienne version en place : un arrêt entre les deux ne perd aucune version
+    let discarded = staging_dir(install_dir);
+    if discarded.exists() {
+        fs::remove_dir_all(&discarded).map_err(|e| format!("Failed to clean staging dir: {}", e))?;
+    }
+    if install_dir.exists() {
+        fs::rename(install_dir, &discarded).map_err(|e| format!("Failed to move current install aside: {}", e))?;
+    }
+    if let Err(e) = fs::rename(&previous, install_dir) {
+        let _ = fs::rename(&discarded, install_dir);
+        return Err(format!("Failed to restore previous install: {}", e));
+    }
+    discard_staging(install_dir);
+
+    info!("Rolled back {} to the previous install", install_dir.display());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn rollback_install(install_dir: String) -> Result<(), String> {
+    rollback(Path::new(&install_dir))
+}
+
+#[tauri::command]
+pub fn has_install_rollback(install_dir: String) -> Result<bool, String> {
+    // Simple lecture : aucune récupération ici, une installation supprimée ne doit pas réapparaître
+    Ok(previous_dir(Path::new(&install_dir)).is_dir())
+}
+
+// Libérer l'espace occupé par le point de retour (désinstallation, version validée)
+#[tauri::command]
+pub fn discard_install_rollback(install_dir: String) -> Result<(), String> {
+    let previous = previous_dir(Path::new(&install_dir));
+    match fs::remove_dir_all(&previous) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove rollback point: {}", e)),
+    }
+}
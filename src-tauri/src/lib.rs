@@ -4,7 +4,17 @@ use tauri_plugin_http::reqwest;
 
 // Modules
 pub mod hash;
+pub mod archive;
 pub mod zip;
+pub mod install_staging;
+pub mod bandwidth;
+pub mod throughput;
+pub mod chunk_scheduler;
+pub mod retry;
+pub mod content_store;
+pub mod download_events;
+pub mod download_group;
+pub mod download_schedule;
 pub mod download_manager;
 
 // Structure pour les événements de progression
@@ -466,8 +476,13 @@ pub fn run() {
             delete_file,
             delete_directory,
             list_directory_contents,
+            zip::extract_archive,
+            zip::extract_archive_async,
             zip::extract_zip_file,
             zip::extract_zip_file_async,
+            install_staging::rollback_install,
+            install_staging::has_install_rollback,
+            install_staging::discard_install_rollback,
             // Commandes du download manager
             download_manager::start_download,
             download_manager::pause_download,
@@ -477,7 +492,30 @@ pub fn run() {
             download_manager::get_download_progress,
             download_manager::get_all_downloads,
             download_manager::cleanup_completed_downloads,
-            download_manager::get_download_stats
+            download_manager::get_download_stats,
+            download_manager::get_bandwidth_limit,
+            download_manager::set_bandwidth_limit,
+            download_manager::clear_bandwidth_limit,
+            download_manager::start_download_group,
+            download_manager::get_download_group,
+            download_manager::get_all_download_groups,
+            download_manager::pause_download_group,
+            download_manager::resume_download_group,
+            download_manager::cancel_download_group,
+            download_manager::remove_download_group,
+            download_manager::list_download_cache,
+            download_manager::prune_download_cache,
+            download_manager::get_download_schedule,
+            download_manager::set_download_schedule,
+            download_manager::schedule_download,
+            download_manager::get_download_config,
+            download_manager::set_download_config,
+            download_manager::get_network_config,
+            download_manager::set_network_config,
+            download_manager::get_download_queue,
+            download_manager::set_download_priority,
+            download_manager::reorder_download_queue,
+            download_manager::move_download_to_front
         ])
         .setup(|app| {
             println!("🚀 Tauri application starting...");
@@ -492,7 +530,12 @@ pub fn run() {
 
             // Initialiser le gestionnaire de téléchargements
             println!("⬇️ Initializing download manager...");
-            let download_manager = download_manager::init_download_manager();
+            let app_local_data_dir = app.path().app_local_data_dir()?;
+            let download_manager = download_manager::init_download_manager(
+                app_local_data_dir.join("cache"),
+                app_local_data_dir.join("config"),
+                app.handle().clone(),
+            );
             app.manage(download_manager);
             println!("✅ Download manager initialized successfully");
 
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+// Attente maximale accordée à un Retry-After, pour qu'un serveur ne bloque pas un téléchargement des heures
+const MAX_RETRY_AFTER_SECS: u64 = 600;
+
+/// Délais entre les tentatives : backoff exponentiel avec jitter, borné par `max_delay`
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(base_delay_ms: u64, max_delay_ms: u64) -> Self {
+        Self {
+            base_delay: Duration::from_millis(base_delay_ms),
+            max_delay: Duration::from_millis(max_delay_ms.max(base_delay_ms)),
+        }
+    }
+
+    // Attente après l'échec n° `attempt` (à partir de 1). Le délai demandé par le serveur
+    // est respecté même s'il dépasse le backoff.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let backoff = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+
+        // Jitter "égal" : au moins la moitié du délai, pour désynchroniser les chunks
+        // qui échouent ensemble sans pour autant réessayer immédiatement
+        let half = backoff / 2;
+        let delay = half + half.mul_f64(fastrand::f64());
+
+        match retry_after {
+            Some(retry_after) => delay.max(retry_after.min(Duration::from_secs(MAX_RETRY_AFTER_SECS))),
+            None => delay,
+        }
+    }
+}
+
+// Un statut HTTP en erreur peut-il disparaître en réessayant ?
+// 404, 403, 410... ne réussiront jamais ; 408, 429 et la plupart des 5xx sont passagers.
+pub fn is_transient_status(status: StatusCode) -> bool {
+    match status {
+        StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::NOT_IMPLEMENTED | StatusCode::HTTP_VERSION_NOT_SUPPORTED => false,
+        status => status.is_server_error(),
+    }
+}
+
+// Retry-After : un nombre de secondes ou une date HTTP
+pub fn parse_retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    // Une date passée autorise une nouvelle tentative immédiate
+    Some((date - now).to_std().unwrap_or(Duration::ZERO))
+}
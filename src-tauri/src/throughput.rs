@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Durée minimale de mesure, pour éviter des pics absurdes juste après le démarrage
+const MIN_SPAN_MS: u64 = 500;
+
+struct MeterState {
+    samples: VecDeque<(Instant, u64)>,
+    started: Instant,
+}
+
+/// Estimateur de débit sur une fenêtre glissante
+pub struct ThroughputMeter {
+    window: Duration,
+    state: Mutex<MeterState>,
+}
+
+impl ThroughputMeter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            state: Mutex::new(MeterState {
+                samples: VecDeque::new(),
+                started: Instant::now(),
+            }),
+        }
+    }
+
+    // Enregistrer des octets reçus
+    pub fn record(&self, bytes: u64) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state.samples.push_back((now, bytes));
+        Self::prune(&mut state, now, self.window);
+    }
+
+    // Débit moyen (octets/s) sur la fenêtre ; tombe à 0 si plus rien n'arrive
+    pub fn bytes_per_second(&self) -> u64 {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        Self::prune(&mut state, now, self.window);
+
+        let total: u64 = state.samples.iter().map(|(_, bytes)| bytes).sum();
+        let span = now
+            .duration_since(state.started)
+            .min(self.window)
+            .max(Duration::from_millis(MIN_SPAN_MS));
+
+        (total as f64 / span.as_secs_f64()) as u64
+    }
+
+    fn prune(state: &mut MeterState, now: Instant, window: Duration) {
+        while let Some((at, _)) = state.samples.front() {
+            if now.duration_since(*at) > window {
+                state.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
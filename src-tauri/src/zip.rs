@@ -1,15 +1,29 @@
 use std::{
     fs::{self, File},
+    io::Read,
     path::{Path, PathBuf},
 };
 
-use ::zip::ZipArchive;
 use tauri::{AppHandle, Emitter};
 use serde::{Deserialize, Serialize};
 use tokio::time::{sleep, Duration};
 use tokio::sync::mpsc;
 use log::{info, debug, warn};
 
+use crate::archive::{Archive, ArchiveEntry, EntryKind};
+use crate::install_staging;
+
+// Longueur maximale lue pour la cible d'un lien symbolique
+const MAX_SYMLINK_TARGET_LEN: u64 = 4096;
+
+// Droits appliqués aux entrées qui n'en stockent pas
+const DEFAULT_FILE_MODE: u32 = 0o644;
+const DEFAULT_DIR_MODE: u32 = 0o755;
+
+// Taille sous laquelle le taux de compression d'une entrée n'est pas contrôlé :
+// un petit fichier très compressible ne peut pas remplir un disque
+const RATIO_CHECK_MIN_BYTES: u64 = 1024 * 1024;
+
 // Structure pour les événements de progression d'extraction
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ExtractionProgress {
@@ -21,73 +35,600 @@ pub struct ExtractionProgress {
     pub status: String, // "extracting", "completed", "failed"
 }
 
-/// Sanitize ZIP file entries to avoid path traversal attacks
-fn sanitize_zip_path(entry: &str) -> Option<PathBuf> {
-    let path = Path::new(entry);
-    if path
-        .components()
-        .any(|c| matches!(c, std::path::Component::ParentDir))
+/// Limites d'une extraction, contre les archives piégées (zip bombs) ou corrompues
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractionLimits {
+    // Taille totale décompressée
+    pub max_total_bytes: u64,
+    pub max_entries: usize,
+    // Rapport taille décompressée / taille compressée d'une entrée, ou de toute l'archive
+    // pour les formats compressés d'un seul bloc
+    pub max_compression_ratio: u64,
+    // Longueur du nom d'une entrée, en octets
+    pub max_path_length: usize,
+}
+
+impl Default for ExtractionLimits {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: 64 * 1024 * 1024 * 1024, // 64GB
+            max_entries: 100_000,
+            // Deflate plafonne vers 1030:1 : un fichier légitime rempli de zéros passe, les
+            // bombes deflate sont bornées par la taille totale et les autres méthodes par ce taux
+            max_compression_ratio: 2000,
+            max_path_length: 1024,
+        }
+    }
+}
+
+/// Limite dépassée
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExtractionLimit {
+    TotalBytes,
+    Entries,
+    CompressionRatio,
+    PathLength,
+}
+
+/// Erreurs d'une extraction
+#[derive(Debug)]
+pub enum ExtractionError {
+    LimitExceeded {
+        limit: ExtractionLimit,
+        // Entrée en cause, si la limite porte sur une entrée
+        entry: Option<String>,
+        actual: u64,
+        max: u64,
+    },
+    Failed(String),
+}
+
+impl std::fmt::Display for ExtractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractionError::LimitExceeded { limit, entry, actual, max } => {
+                let name = match limit {
+                    ExtractionLimit::TotalBytes => "total uncompressed size",
+                    ExtractionLimit::Entries => "entry count",
+                    ExtractionLimit::CompressionRatio => "compression ratio",
+                    ExtractionLimit::PathLength => "path length",
+                };
+                write!(f, "Archive exceeds the {} limit ({} > {})", name, actual, max)?;
+                if let Some(entry) = entry {
+                    write!(f, " at entry {}", entry)?;
+                }
+                Ok(())
+            }
+            ExtractionError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<String> for ExtractionError {
+    fn from(e: String) -> Self {
+        ExtractionError::Failed(e)
+    }
+}
+
+impl ExtractionLimits {
+    fn exceeded(limit: ExtractionLimit, entry: Option<&str>, actual: u64, max: u64) -> ExtractionError {
+        ExtractionError::LimitExceeded {
+            limit,
+            entry: entry.map(|e| e.to_string()),
+            actual,
+            max,
+        }
+    }
+
+    fn check_ratio(&self, name: &str, size: u64, compressed_size: u64) -> Result<(), ExtractionError> {
+        if size >= RATIO_CHECK_MIN_BYTES && size / compressed_size.max(1) > self.max_compression_ratio {
+            return Err(Self::exceeded(
+                ExtractionLimit::CompressionRatio,
+                Some(name),
+                size / compressed_size.max(1),
+                self.max_compression_ratio,
+            ));
+        }
+        Ok(())
+    }
+
+    // Contrôles faisables sur une entrée avant de la décompresser
+    fn check_entry(&self, name: &str, size: u64, compressed_size: Option<u64>) -> Result<(), ExtractionError> {
+        if name.len() > self.max_path_length {
+            return Err(Self::exceeded(
+                ExtractionLimit::PathLength,
+                Some(name),
+                name.len() as u64,
+                self.max_path_length as u64,
+            ));
+        }
+        match compressed_size {
+            Some(compressed_size) => self.check_ratio(name, size, compressed_size),
+            None => Ok(()),
+        }
+    }
+
+    // Octets qu'une entrée peut réellement produire : les tailles annoncées ne sont pas fiables.
+    // Sans taille compressée, seule la taille totale borne l'entrée.
+    fn entry_budget(&self, compressed_size: Option<u64>, remaining_total: u64) -> u64 {
+        let ratio_budget = compressed_size.map_or(u64::MAX, |compressed_size| {
+            compressed_size
+                .saturating_mul(self.max_compression_ratio)
+                .max(RATIO_CHECK_MIN_BYTES)
+        });
+        ratio_budget.min(remaining_total)
+    }
+}
+
+// Vérifier les tailles annoncées de toute l'archive avant d'écrire quoi que ce soit.
+// Renvoie le nombre d'entrées.
+fn check_archive_limits(archive: &Archive, limits: &ExtractionLimits) -> Result<usize, ExtractionError> {
+    let mut count: usize = 0;
+    let mut total: u64 = 0;
+    // Entrées sans taille compressée propre : leur taux se mesure sur tout le fichier d'archive,
+    // au fil du parcours pour arrêter une bombe avant de la décompresser entièrement
+    let mut solid_total: u64 = 0;
+    archive.scan(|entry: &ArchiveEntry| {
+        count += 1;
+        if count > limits.max_entries {
+            return Err(ExtractionLimits::exceeded(
+                ExtractionLimit::Entries,
+                None,
+                count as u64,
+                limits.max_entries as u64,
+            ));
+        }
+
+        limits.check_entry(&entry.name, entry.size, entry.compressed_size)?;
+        if entry.compressed_size.is_none() {
+            solid_total = solid_total.saturating_add(entry.size);
+            limits.check_ratio(&entry.name, solid_total, archive.file_size())?;
+        }
+
+        total = total.saturating_add(entry.size);
+        if total > limits.max_total_bytes {
+            return Err(ExtractionLimits::exceeded(
+                ExtractionLimit::TotalBytes,
+                Some(&entry.name),
+                total,
+                limits.max_total_bytes,
+            ));
+        }
+        Ok(())
+    })?;
+    Ok(count)
+}
+
+/// Raison du rejet d'une entrée de l'archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RejectionReason {
+    // Nom vide ou contenant un octet nul
+    InvalidName,
+    // Chemin absolu, racine ou préfixe de lecteur (`/etc/x`, `C:\x`, `\\serveur\x`)
+    AbsolutePath,
+    // `..` qui remonte au-dessus du dossier d'extraction
+    ParentTraversal,
+    // Lien symbolique dont la cible sort du dossier d'extraction
+    SymlinkOutsideTarget,
+    // Liens symboliques non pris en charge sur cette plateforme
+    SymlinkUnsupported,
+    // Écriture à travers (ou par-dessus) un lien symbolique extrait plus tôt
+    ThroughSymlink,
+    // Lien physique, périphérique, tube nommé...
+    UnsupportedEntryType,
+}
+
+/// Entrée ignorée pendant l'extraction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedEntry {
+    pub name: String,
+    pub reason: RejectionReason,
+}
+
+/// Bilan d'une extraction réussie
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractionReport {
+    pub files_extracted: usize,
+    pub symlinks_created: usize,
+    pub rejected: Vec<RejectedEntry>,
+}
+
+impl ExtractionReport {
+    fn reject(&mut self, name: &str, reason: RejectionReason) {
+        warn!("Rejected archive entry {}: {:?}", name, reason);
+        self.rejected.push(RejectedEntry {
+            name: name.to_string(),
+            reason,
+        });
+    }
+}
+
+// Découper un chemin d'archive en composants normaux, sans `.` ni `..`. Les deux séparateurs
+// sont acceptés et les préfixes Windows rejetés quelle que soit la plateforme, pour qu'une
+// même archive soit traitée de la même façon partout.
+fn normalize_components(path: &str) -> Result<Vec<&str>, RejectionReason> {
+    if path.contains('\0') {
+        return Err(RejectionReason::InvalidName);
+    }
+    if path.starts_with(['/', '\\']) {
+        return Err(RejectionReason::AbsolutePath);
+    }
+
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop().ok_or(RejectionReason::ParentTraversal)?;
+            }
+            // Lecteur ("C:") ou flux alternatif NTFS ("file:stream")
+            component if component.contains(':') => return Err(RejectionReason::AbsolutePath),
+            component => components.push(component),
+        }
+    }
+    Ok(components)
+}
+
+/// Chemin relatif sûr d'une entrée, dans les limites du dossier d'extraction
+fn sanitize_entry_path(entry: &str) -> Result<PathBuf, RejectionReason> {
+    let components = normalize_components(entry)?;
+    if components.is_empty() {
+        return Err(RejectionReason::InvalidName);
+    }
+    Ok(components.iter().collect())
+}
+
+// Un lien n'est créé que si sa cible, relative, reste dans le dossier d'extraction.
+// Les `..` ne sont admis qu'en tête : après un autre composant, ils pourraient remonter
+// depuis la cible d'un autre lien plutôt que depuis le dossier qui le contient.
+fn check_symlink_target(link: &Path, target: &str) -> Result<(), RejectionReason> {
+    if target.starts_with(['/', '\\']) || target.contains(':') {
+        return Err(RejectionReason::SymlinkOutsideTarget);
+    }
+
+    let mut seen_normal = false;
+    for component in target.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." if seen_normal => return Err(RejectionReason::SymlinkOutsideTarget),
+            ".." => {}
+            _ => seen_normal = true,
+        }
+    }
+
+    // Résoudre la cible depuis le dossier du lien, relatif au dossier d'extraction
+    let mut resolved = link.parent().map(|p| p.to_string_lossy().replace('\\', "/")).unwrap_or_default();
+    if !resolved.is_empty() {
+        resolved.push('/');
+    }
+    resolved.push_str(target);
+    match normalize_components(&resolved) {
+        Ok(_) if !target.is_empty() => Ok(()),
+        Ok(_) => Err(RejectionReason::InvalidName),
+        Err(_) => Err(RejectionReason::SymlinkOutsideTarget),
+    }
+}
+
+// Un composant déjà extrait du chemin (ou le chemin lui-même) est-il un lien symbolique ?
+fn passes_through_symlink(target: &Path, relative_path: &Path) -> bool {
+    let mut current = target.to_path_buf();
+    relative_path.components().any(|component| {
+        current.push(component);
+        fs::symlink_metadata(&current).is_ok_and(|m| m.file_type().is_symlink())
+    })
+}
+
+#[cfg(unix)]
+fn create_symlink(link_target: &str, out_path: &Path) -> Result<(), RejectionReason> {
+    std::os::unix::fs::symlink(link_target, out_path).map_err(|e| {
+        warn!("Failed to create symlink {}: {}", out_path.display(), e);
+        RejectionReason::SymlinkUnsupported
+    })
+}
+
+// Créer des liens sous Windows demande des privilèges ; l'entrée est signalée dans le bilan
+#[cfg(not(unix))]
+fn create_symlink(_link_target: &str, _out_path: &Path) -> Result<(), RejectionReason> {
+    Err(RejectionReason::SymlinkUnsupported)
+}
+
+// Droits Unix d'un fichier : ceux de l'archive, sans setuid/setgid/sticky et toujours lisible
+// par son propriétaire
+fn file_mode(stored: Option<u32>) -> u32 {
+    stored.map_or(DEFAULT_FILE_MODE, |mode| (mode & 0o777) | 0o400)
+}
+
+// Un dossier doit rester traversable et modifiable par son propriétaire, sans quoi
+// l'installation ne pourrait plus être remplacée ni supprimée
+fn dir_mode(stored: Option<u32>) -> u32 {
+    stored.map_or(DEFAULT_DIR_MODE, |mode| (mode & 0o777) | 0o700)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+// Les droits Unix n'ont pas d'équivalent : seule la date de modification est restaurée
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+// Rendre exécutable le binaire déclaré par le manifeste, quels que soient les droits de l'archive
+fn mark_executable(target: &Path, executable: &str) -> Result<(), String> {
+    let relative_path = sanitize_entry_path(executable)
+        .map_err(|reason| format!("Invalid executable path {}: {:?}", executable, reason))?;
+    if passes_through_symlink(target, &relative_path) {
+        return Err(format!("Executable {} is behind a symbolic link", executable));
+    }
+
+    let path = target.join(&relative_path);
+    let metadata = match fs::metadata(&path) {
+        Ok(metadata) => metadata,
+        // Le lanceur cherche d'autres exécutables : une archive sans celui-ci reste installable
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            warn!("Executable {} not found in archive", executable);
+            return Ok(());
+        }
+        Err(e) => return Err(format!("Failed to read executable {}: {}", executable, e)),
+    };
+    if !metadata.is_file() {
+        return Err(format!("Executable {} is not a file", executable));
+    }
+
+    #[cfg(unix)]
     {
-        None
-    } else {
-        Some(path.to_path_buf())
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode() & 0o777;
+        // Exécutable par ceux qui peuvent le lire
+        set_mode(&path, mode | 0o100 | ((mode & 0o044) >> 2))
+            .map_err(|e| format!("Failed to make {} executable: {}", executable, e))?;
     }
+    Ok(())
 }
 
-// Version synchrone simple
-#[tauri::command]
-pub fn extract_zip_file(file_path: String, extract_to: String) -> Result<(), String> {
-    let zip_file = File::open(&file_path).map_err(|e| format!("Failed to open zip: {}", e))?;
-    let mut archive =
-        ZipArchive::new(zip_file).map_err(|e| format!("Invalid zip archive: {}", e))?;
-
-    fs::create_dir_all(&extract_to)
-        .map_err(|e| format!("Failed to create output dir: {}", e))?;
-
-    let total_files = archive.len();
-    info!("Starting extraction of {} files to: {}", total_files, extract_to);
-
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Cannot access entry: {}", e))?;
-        let entry_name = file.name().to_string();
-
-        let relative_path = match sanitize_zip_path(&entry_name) {
-            Some(path) => path,
-            None => {
-                warn!("Skipped suspicious file path: {}", entry_name);
-                continue;
+// État d'une extraction en cours dans `target`
+struct Extractor<'a> {
+    target: &'a Path,
+    limits: &'a ExtractionLimits,
+    // Fichiers écrits avec leur taille, contrôlés avant la mise en place
+    extracted: Vec<(PathBuf, u64)>,
+    report: ExtractionReport,
+    total_written: u64,
+    // Droits des dossiers, appliqués une fois leur contenu écrit
+    dir_modes: Vec<(PathBuf, u32)>,
+}
+
+impl Extractor<'_> {
+    fn extract_entry(&mut self, entry: &ArchiveEntry, content: &mut dyn Read) -> Result<(), ExtractionError> {
+        let entry_name = entry.name.as_str();
+
+        // La racine elle-même ("./" en tête de nombreux tar) n'a rien à créer
+        if entry.kind == EntryKind::Directory && normalize_components(entry_name).is_ok_and(|c| c.is_empty()) {
+            return Ok(());
+        }
+
+        let relative_path = match sanitize_entry_path(entry_name) {
+            Ok(path) => path,
+            Err(reason) => {
+                self.report.reject(entry_name, reason);
+                return Ok(());
             }
         };
 
-        let out_path = Path::new(&extract_to).join(&relative_path);
+        if passes_through_symlink(self.target, &relative_path) {
+            self.report.reject(entry_name, RejectionReason::ThroughSymlink);
+            return Ok(());
+        }
+
+        let out_path = self.target.join(&relative_path);
+
+        match entry.kind {
+            EntryKind::Unsupported => self.report.reject(entry_name, RejectionReason::UnsupportedEntryType),
+            EntryKind::Symlink => {
+                let link_target = match &entry.link_target {
+                    Some(link_target) => link_target.clone(),
+                    None => {
+                        let mut link_target = String::new();
+                        content
+                            .take(MAX_SYMLINK_TARGET_LEN)
+                            .read_to_string(&mut link_target)
+                            .map_err(|e| format!("Failed to read symlink {}: {}", entry_name, e))?;
+                        link_target
+                    }
+                };
+
+                if let Some(parent) = out_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                match check_symlink_target(&relative_path, &link_target)
+                    .and_then(|()| create_symlink(&link_target, &out_path))
+                {
+                    Ok(()) => {
+                        self.report.symlinks_created += 1;
+                        debug!("Created symlink: {} -> {}", entry_name, link_target);
+                    }
+                    Err(reason) => self.report.reject(entry_name, reason),
+                }
+            }
+            EntryKind::Directory => {
+                fs::create_dir_all(&out_path).map_err(|e| e.to_string())?;
+                self.dir_modes.push((out_path, dir_mode(entry.mode)));
+                debug!("Created directory: {}", entry_name);
+            }
+            EntryKind::File => {
+                if let Some(parent) = out_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let mut outfile = File::create(&out_path).map_err(|e| e.to_string())?;
+
+                // Ne jamais décompresser plus que le budget de l'entrée, quoi qu'annonce l'archive
+                let remaining_total = self.limits.max_total_bytes.saturating_sub(self.total_written);
+                let budget = self.limits.entry_budget(entry.compressed_size, remaining_total);
+                // La lecture jusqu'au bout de l'entrée vérifie aussi son CRC-32
+                let written = std::io::copy(&mut content.take(budget.saturating_add(1)), &mut outfile)
+                    .map_err(|e| format!("Failed to extract {}: {}", entry_name, e))?;
+                if written > budget {
+                    let (limit, actual, max) = if written > remaining_total {
+                        (ExtractionLimit::TotalBytes, self.total_written + written, self.limits.max_total_bytes)
+                    } else {
+                        (
+                            ExtractionLimit::CompressionRatio,
+                            written / entry.compressed_size.unwrap_or(1).max(1),
+                            self.limits.max_compression_ratio,
+                        )
+                    };
+                    return Err(ExtractionLimits::exceeded(limit, Some(entry_name), actual, max));
+                }
+                if written != entry.size {
+                    return Err(format!("Entry {} is truncated ({} of {} bytes)", entry_name, written, entry.size).into());
+                }
+                self.total_written += written;
 
-        if file.is_dir() {
-            fs::create_dir_all(&out_path).map_err(|e| e.to_string())?;
-            debug!("Created directory: {}", entry_name);
-        } else {
-            if let Some(parent) = out_path.parent() {
-                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                if let Some(modified) = entry.modified {
+                    if let Err(e) = outfile.set_modified(modified) {
+                        warn!("Failed to restore modification time of {}: {}", entry_name, e);
+                    }
+                }
+                drop(outfile);
+                set_mode(&out_path, file_mode(entry.mode))
+                    .map_err(|e| format!("Failed to set permissions of {}: {}", entry_name, e))?;
+                self.extracted.push((relative_path, written));
+                self.report.files_extracted += 1;
+                debug!("Extracted file: {}", entry_name);
             }
-            let mut outfile = File::create(&out_path).map_err(|e| e.to_string())?;
-            std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
-            debug!("Extracted file: {}", entry_name);
         }
+        Ok(())
     }
 
-    info!("Extraction completed successfully!");
+    fn finish(mut self) -> Result<(Vec<(PathBuf, u64)>, ExtractionReport), ExtractionError> {
+        // Les plus profonds d'abord : un dossier en lecture seule n'empêche pas de traiter ses sous-dossiers
+        self.dir_modes.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+        for (path, mode) in self.dir_modes {
+            set_mode(&path, mode).map_err(|e| format!("Failed to set permissions of {}: {}", path.display(), e))?;
+        }
+        Ok((self.extracted, self.report))
+    }
+}
+
+// Extraire l'archive, quel que soit son format, dans `target`. `on_progress(entrées traitées,
+// total, entrée courante)` est appelé au début puis après chaque entrée. Renvoie les fichiers
+// écrits avec leur taille, et le bilan de l'extraction.
+fn extract_archive_to(
+    file_path: &str,
+    target: &Path,
+    limits: &ExtractionLimits,
+    mut on_progress: impl FnMut(usize, usize, &str),
+) -> Result<(Vec<(PathBuf, u64)>, ExtractionReport), ExtractionError> {
+    let archive = Archive::open(Path::new(file_path))?;
+    let total_files = check_archive_limits(&archive, limits)?;
+
+    info!(
+        "Starting extraction of {} entries ({:?}) to: {}",
+        total_files,
+        archive.format(),
+        target.display()
+    );
+    on_progress(0, total_files, "");
+
+    let mut extractor = Extractor {
+        target,
+        limits,
+        extracted: Vec::new(),
+        report: ExtractionReport::default(),
+        total_written: 0,
+        dir_modes: Vec::new(),
+    };
+    let mut processed = 0;
+    archive.extract(|entry: &ArchiveEntry, content: &mut dyn Read| {
+        extractor.extract_entry(entry, content)?;
+        processed += 1;
+        on_progress(processed, total_files, &entry.name);
+        Ok::<(), ExtractionError>(())
+    })?;
+
+    extractor.finish()
+}
+
+// Contrôler la version préparée avant de la mettre en place
+fn verify_extracted(target: &Path, files: &[(PathBuf, u64)]) -> Result<(), String> {
+    for (relative_path, size) in files {
+        let metadata = fs::metadata(target.join(relative_path))
+            .map_err(|e| format!("Extracted file {} is missing: {}", relative_path.display(), e))?;
+        if metadata.len() != *size {
+            return Err(format!(
+                "Extracted file {} has {} bytes instead of {}",
+                relative_path.display(),
+                metadata.len(),
+                size
+            ));
+        }
+    }
     Ok(())
 }
 
+/// Extraire dans un dossier frère, vérifier, puis remplacer l'installation d'un seul renommage.
+/// En cas d'échec, l'installation en place reste intacte.
+/// `executable` : chemin, relatif à l'installation, du binaire à rendre exécutable.
+pub fn install_archive(
+    file_path: &str,
+    extract_to: &str,
+    limits: &ExtractionLimits,
+    executable: Option<&str>,
+    on_progress: impl FnMut(usize, usize, &str),
+) -> Result<ExtractionReport, ExtractionError> {
+    let install_dir = Path::new(extract_to);
+    let staging = install_staging::prepare_staging(install_dir)?;
+
+    let result = extract_archive_to(file_path, &staging, limits, on_progress)
+        .and_then(|(files, report)| {
+            verify_extracted(&staging, &files)?;
+            if let Some(executable) = executable {
+                mark_executable(&staging, executable)?;
+            }
+            Ok(report)
+        });
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            install_staging::discard_staging(install_dir);
+            return Err(e);
+        }
+    };
+
+    install_staging::commit_staging(install_dir)?;
+    Ok(report)
+}
+
+// Version synchrone simple ; le format (zip, tar, tar.gz, tar.xz, tar.zst, 7z) est reconnu au contenu
+#[tauri::command]
+pub fn extract_archive(
+    file_path: String,
+    extract_to: String,
+    limits: Option<ExtractionLimits>,
+    executable: Option<String>,
+) -> Result<ExtractionReport, String> {
+    let limits = limits.unwrap_or_default();
+    let report = install_archive(&file_path, &extract_to, &limits, executable.as_deref(), |_, _, _| {})
+        .map_err(|e| e.to_string())?;
+
+    info!("Extraction completed successfully!");
+    Ok(report)
+}
+
 // Version asynchrone avec événements de progression
 #[tauri::command]
-pub async fn extract_zip_file_async(
+pub async fn extract_archive_async(
     file_path: String,
     extract_to: String,
     extraction_id: String,
+    limits: Option<ExtractionLimits>,
+    executable: Option<String>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> Result<ExtractionReport, String> {
     // Créer un channel pour communiquer les événements de progression
     let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<ExtractionProgress>();
 
@@ -111,80 +652,52 @@ pub async fn extract_zip_file_async(
     let progress_tx_clone = progress_tx.clone();
     
     // Spawner la tâche d'extraction en parallèle avec la tâche d'émission d'événements
-    let extraction_task = tokio::task::spawn_blocking(move || -> Result<(), String> {
-        let zip_file = File::open(&file_path_clone).map_err(|e| format!("Failed to open zip: {}", e))?;
-        let mut archive = ZipArchive::new(zip_file).map_err(|e| format!("Invalid zip archive: {}", e))?;
+    let extraction_task = tokio::task::spawn_blocking(move || -> Result<ExtractionReport, String> {
+        let mut last = (0, 0);
+        let limits = limits.unwrap_or_default();
+        let result = install_archive(
+            &file_path_clone,
+            &extract_to_clone,
+            &limits,
+            executable.as_deref(),
+            |files_processed, total_files, current_file| {
+                last = (files_processed, total_files);
+                let percentage = if total_files > 0 {
+                    (files_processed as f64 / total_files as f64) * 100.0
+                } else {
+                    0.0
+                };
 
-        fs::create_dir_all(&extract_to_clone)
-            .map_err(|e| format!("Failed to create output dir: {}", e))?;
+                let _ = progress_tx_clone.send(ExtractionProgress {
+                    extraction_id: extraction_id_clone.clone(),
+                    current_file: current_file.to_string(),
+                    files_processed,
+                    total_files,
+                    percentage,
+                    status: "extracting".to_string(),
+                });
+            },
+        );
 
-        let total_files = archive.len();
-        info!("Starting async extraction of {} files to: {}", total_files, extract_to_clone);
-
-        // Envoyer la progression initiale avec le nombre total de fichiers
+        // Envoyer la completion, ou l'échec : l'installation en place n'a alors pas changé
+        let (files_processed, total_files) = last;
+        let (percentage, status) = match &result {
+            Ok(_) => (100.0, "completed"),
+            Err(_) => (0.0, "failed"),
+        };
         let _ = progress_tx_clone.send(ExtractionProgress {
             extraction_id: extraction_id_clone.clone(),
             current_file: String::new(),
-            files_processed: 0,
+            files_processed,
             total_files,
-            percentage: 0.0,
-            status: "extracting".to_string(),
+            percentage,
+            status: status.to_string(),
         });
 
-        for i in 0..archive.len() {
-            let mut file = archive
-                .by_index(i)
-                .map_err(|e| format!("Cannot access entry: {}", e))?;
-            let entry_name = file.name().to_string();
-
-            let relative_path = match sanitize_zip_path(&entry_name) {
-                Some(path) => path,
-                None => {
-                    warn!("Skipped suspicious file path: {}", entry_name);
-                    continue;
-                }
-            };
-
-            let out_path = Path::new(&extract_to_clone).join(&relative_path);
-
-            if file.is_dir() {
-                fs::create_dir_all(&out_path).map_err(|e| e.to_string())?;
-                debug!("Created directory: {}", entry_name);
-            } else {
-                if let Some(parent) = out_path.parent() {
-                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-                }
-                let mut outfile = File::create(&out_path).map_err(|e| e.to_string())?;
-                std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
-                debug!("Extracted file: {}", entry_name);
-            }
-
-            // Calculer et envoyer la progression
-            let files_processed = i + 1;
-            let percentage = (files_processed as f64 / total_files as f64) * 100.0;
-
-            let _ = progress_tx_clone.send(ExtractionProgress {
-                extraction_id: extraction_id_clone.clone(),
-                current_file: entry_name,
-                files_processed,
-                total_files,
-                percentage,
-                status: "extracting".to_string(),
-            });
+        if result.is_ok() {
+            info!("Async extraction completed successfully!");
         }
-
-        // Envoyer la completion
-        let _ = progress_tx_clone.send(ExtractionProgress {
-            extraction_id: extraction_id_clone.clone(),
-            current_file: String::new(),
-            files_processed: total_files,
-            total_files,
-            percentage: 100.0,
-            status: "completed".to_string(),
-        });
-
-        info!("Async extraction completed successfully!");
-        Ok(())
+        result.map_err(|e| e.to_string())
     });
 
     // Tâche pour émettre les événements de progression
@@ -208,3 +721,26 @@ pub async fn extract_zip_file_async(
 
     extraction_result
 }
+
+// Anciens noms des commandes, conservés pour les appels existants : tous les formats sont acceptés
+#[tauri::command]
+pub fn extract_zip_file(
+    file_path: String,
+    extract_to: String,
+    limits: Option<ExtractionLimits>,
+    executable: Option<String>,
+) -> Result<ExtractionReport, String> {
+    extract_archive(file_path, extract_to, limits, executable)
+}
+
+#[tauri::command]
+pub async fn extract_zip_file_async(
+    file_path: String,
+    extract_to: String,
+    extraction_id: String,
+    limits: Option<ExtractionLimits>,
+    executable: Option<String>,
+    app: AppHandle,
+) -> Result<ExtractionReport, String> {
+    extract_archive_async(file_path, extract_to, extraction_id, limits, executable, app).await
+}
@@ -0,0 +1,934 @@
+// Tests d'intégration du gestionnaire de téléchargements contre un serveur HTTP simulé
+
+mod support;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use app_lib::chunk_scheduler::ChunkScheduler;
+use app_lib::content_store::ContentStore;
+use app_lib::download_events::DownloadEvent;
+use app_lib::download_manager::{
+    ChunkProgress, ChunkStatus, DownloadConfig, DownloadManager, DownloadOverrides, DownloadRequest, DownloadStatus,
+    FailureReason,
+};
+use support::{sha256_hex, test_config, test_content, wait_for_outcome, Behavior, MockServer, TempDir};
+
+fn manager_with_events(config: DownloadConfig) -> (DownloadManager, UnboundedReceiver<DownloadEvent>) {
+    let (sender, events) = mpsc::unbounded_channel();
+    (DownloadManager::new(config).with_event_sink(Arc::new(sender)), events)
+}
+
+fn request(url: String, file_path: String) -> DownloadRequest {
+    DownloadRequest {
+        url,
+        file_path,
+        ..Default::default()
+    }
+}
+
+// Attendre que le transfert ait écrit quelques octets
+async fn wait_for_progress(manager: &DownloadManager, download_id: &str) {
+    tokio::time::timeout(Duration::from_secs(30), async {
+        loop {
+            if manager
+                .get_download_progress(download_id)
+                .await
+                .is_some_and(|p| p.downloaded > 0 && p.status == DownloadStatus::Downloading)
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("download made no progress");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn chunked_download_is_byte_exact() {
+    // Taille volontairement non multiple de la taille des chunks
+    let content = test_content(1_000_003, 1);
+    let server = MockServer::start(content.clone(), Behavior::default()).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(progress.total_size, Some(content.len() as u64));
+    assert!(!progress.chunks.is_empty());
+    assert!(server.range_starts().len() > 1);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn server_without_range_support_uses_single_stream() {
+    let content = test_content(300_007, 2);
+    let behavior = Behavior {
+        ranges: false,
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert!(progress.chunks.is_empty());
+    assert_eq!(server.get_count(), 1);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn unknown_size_is_read_until_the_connection_closes() {
+    let content = test_content(200_003, 3);
+    let behavior = Behavior {
+        ranges: false,
+        content_length: false,
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(progress.total_size, Some(content.len() as u64));
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn dropped_connections_mid_chunk_are_resumed() {
+    let content = test_content(700_001, 4);
+    let behavior = Behavior {
+        drop_first_gets: 3,
+        drop_after_bytes: 10_000,
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    // Les chunks coupés reprennent à leur offset au lieu de repartir du début
+    assert!(server.range_starts().iter().any(|start| start % (64 * 1024) != 0));
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn dropped_connection_without_ranges_restarts_from_zero() {
+    let content = test_content(250_001, 5);
+    let behavior = Behavior {
+        ranges: false,
+        drop_first_gets: 1,
+        drop_after_bytes: 50_000,
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn stalled_connections_hit_the_idle_timeout() {
+    let content = test_content(400_013, 16);
+    let behavior = Behavior {
+        drop_first_gets: 2,
+        drop_after_bytes: 20_000,
+        stall_instead_of_drop: true,
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let config = DownloadConfig {
+        read_timeout_seconds: 1,
+        ..test_config()
+    };
+    let (manager, mut events) = manager_with_events(config);
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn silent_server_fails_the_probe_after_the_idle_timeout() {
+    // Connexions acceptées mais jamais de réponse, pas même les en-têtes
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            connections.push(stream);
+        }
+    });
+
+    let dir = TempDir::new();
+    let config = DownloadConfig {
+        read_timeout_seconds: 1,
+        ..test_config()
+    };
+    let manager = DownloadManager::new(config);
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        manager.start_download(request(format!("http://{}/game.zip", address), dir.file("game.zip"))),
+    )
+    .await
+    .expect("probe hung on a silent server");
+    assert!(result.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn per_download_overrides_replace_the_global_config() {
+    let content = test_content(500_003, 17);
+    let server = MockServer::start(content.clone(), Behavior::default()).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    // Des chunks plus grands que le fichier : un seul flux, sans requête partielle
+    let id = manager
+        .start_download(DownloadRequest {
+            overrides: DownloadOverrides {
+                chunk_size: Some(1024 * 1024),
+                ..Default::default()
+            },
+            ..request(server.url("game.zip"), dir.file("game.zip"))
+        })
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert!(server.range_starts().is_empty());
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+
+    let invalid = manager
+        .start_download(DownloadRequest {
+            overrides: DownloadOverrides {
+                max_retries: Some(0),
+                ..Default::default()
+            },
+            ..request(server.url("other.zip"), dir.file("other.zip"))
+        })
+        .await;
+    assert!(invalid.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn slow_peer_completes_with_work_stealing() {
+    let content = test_content(600_011, 6);
+    let behavior = Behavior {
+        bytes_per_second: Some(512 * 1024),
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let config = DownloadConfig {
+        // Peu de chunks planifiés : les workers inactifs doivent voler des plages
+        chunk_size: 256 * 1024,
+        ..test_config()
+    };
+    let (manager, mut events) = manager_with_events(config);
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn server_error_burst_is_retried() {
+    let content = test_content(400_009, 7);
+    let behavior = Behavior {
+        fail_first_gets: 4,
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn persistent_server_errors_report_failed_ranges() {
+    let content = test_content(300_001, 8);
+    let behavior = Behavior {
+        fail_first_gets: usize::MAX,
+        ..Default::default()
+    };
+    let server = MockServer::start(content, behavior).await;
+    let dir = TempDir::new();
+    let config = DownloadConfig {
+        max_retries: 2,
+        ..test_config()
+    };
+    let (manager, mut events) = manager_with_events(config);
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Failed);
+    assert_eq!(progress.failure_reason, Some(FailureReason::Transfer));
+    let missing: u64 = progress.failed_ranges.iter().map(|r| r.end + 1 - r.start).sum();
+    assert_eq!(missing, 300_001);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn permanent_http_errors_are_not_retried() {
+    let content = test_content(150_001, 18);
+    let behavior = Behavior {
+        ranges: false,
+        fail_first_gets: usize::MAX,
+        fail_status: "404 Not Found",
+        ..Default::default()
+    };
+    let server = MockServer::start(content, behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Failed);
+    assert!(progress.error.as_deref().unwrap_or_default().contains("404"), "{:?}", progress.error);
+    assert_eq!(server.get_count(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn retry_after_is_honoured_and_reported() {
+    let content = test_content(150_011, 19);
+    let behavior = Behavior {
+        ranges: false,
+        fail_first_gets: 1,
+        fail_status: "429 Too Many Requests",
+        retry_after: Some("1".to_string()),
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let started = std::time::Instant::now();
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+
+    // Pendant l'attente, l'erreur explique la nouvelle tentative
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let waiting = manager.get_download_progress(&id).await.unwrap();
+    assert_eq!(waiting.status, DownloadStatus::Downloading);
+    assert!(waiting.error.as_deref().unwrap_or_default().contains("429"), "{:?}", waiting.error);
+
+    let progress = wait_for_outcome(&mut events, &id).await;
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(progress.error, None);
+    // Le délai de base (10 ms) est remplacé par celui demandé par le serveur
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn unavailable_primary_falls_back_to_mirror() {
+    let content = test_content(350_003, 9);
+    let primary = MockServer::start(
+        content.clone(),
+        Behavior {
+            fail_first_gets: usize::MAX,
+            ..Default::default()
+        },
+    )
+    .await;
+    let mirror = MockServer::start(content.clone(), Behavior::default()).await;
+    let dir = TempDir::new();
+    let config = DownloadConfig {
+        max_retries: 2,
+        ..test_config()
+    };
+    let (manager, mut events) = manager_with_events(config);
+
+    let id = manager
+        .start_download(DownloadRequest {
+            mirrors: vec![mirror.url("game.zip")],
+            ..request(primary.url("game.zip"), dir.file("game.zip"))
+        })
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert!(mirror.get_count() > 0);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn credentials_are_not_sent_to_mirrors_of_another_origin() {
+    let content = test_content(350_011, 22);
+    let primary = MockServer::start(
+        content.clone(),
+        Behavior {
+            fail_first_gets: usize::MAX,
+            ..Default::default()
+        },
+    )
+    .await;
+    let mirror = MockServer::start(content.clone(), Behavior::default()).await;
+    let dir = TempDir::new();
+    let config = DownloadConfig {
+        max_retries: 2,
+        ..test_config()
+    };
+    let journal = dir.path().join("downloads.json");
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let manager = DownloadManager::new(config)
+        .with_journal(journal.clone())
+        .with_event_sink(Arc::new(sender));
+
+    let id = manager
+        .start_download(DownloadRequest {
+            mirrors: vec![mirror.url("game.zip")],
+            headers: HashMap::from([("Authorization".to_string(), "Bearer secret".to_string())]),
+            ..request(primary.url("game.zip"), dir.file("game.zip"))
+        })
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert!(mirror.get_count() > 0);
+    assert!(!primary.authorizations().is_empty());
+    assert!(mirror.authorizations().is_empty());
+    // Ni les événements ni le journal ne contiennent les headers
+    assert!(!serde_json::to_string(&progress).unwrap().contains("secret"));
+    assert!(!std::fs::read_to_string(journal).unwrap().contains("secret"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn pause_and_resume_continue_from_saved_offsets() {
+    let content = test_content(800_021, 10);
+    let behavior = Behavior {
+        bytes_per_second: Some(256 * 1024),
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    wait_for_progress(&manager, &id).await;
+
+    manager.pause_download(&id).await.unwrap();
+    let paused = manager.get_download_progress(&id).await.unwrap();
+    assert_eq!(paused.status, DownloadStatus::Paused);
+    let gets_before_resume = server.get_count();
+
+    manager.resume_download(id.clone()).await.unwrap();
+    // Une reprise sans effet n'est pas signalée comme réussie
+    assert!(manager.resume_download(id.clone()).await.is_err());
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert!(server.get_count() > gets_before_resume);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn file_deleted_while_paused_is_downloaded_again() {
+    let content = test_content(800_029, 14);
+    let behavior = Behavior {
+        bytes_per_second: Some(256 * 1024),
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(DownloadRequest {
+            expected_sha256: Some(sha256_hex(&content)),
+            ..request(server.url("game.zip"), dir.file("game.zip"))
+        })
+        .await
+        .unwrap();
+
+    // Attendre qu'au moins un chunk soit marqué terminé avant de supprimer le fichier
+    tokio::time::timeout(Duration::from_secs(30), async {
+        loop {
+            let progress = manager.get_download_progress(&id).await.unwrap();
+            if progress.chunks.iter().any(|c| c.status == ChunkStatus::Completed) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("no chunk completed");
+
+    manager.pause_download(&id).await.unwrap();
+    std::fs::remove_file(dir.file("game.zip")).unwrap();
+
+    manager.resume_download(id.clone()).await.unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn interrupted_download_resumes_after_restart_from_journal() {
+    let content = test_content(800_017, 11);
+    let behavior = Behavior {
+        bytes_per_second: Some(256 * 1024),
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let journal = dir.path().join("downloads.json");
+
+    let id = {
+        let manager = DownloadManager::new(test_config()).with_journal(journal.clone());
+        let id = manager
+            .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+            .await
+            .unwrap();
+        wait_for_progress(&manager, &id).await;
+        manager.pause_download(&id).await.unwrap();
+        id
+    };
+
+    // Nouveau processus : le journal redonne l'état du téléchargement
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let manager = DownloadManager::new(test_config())
+        .with_journal(journal)
+        .with_event_sink(Arc::new(sender));
+    let restored = manager.get_download_progress(&id).await.unwrap();
+    assert_eq!(restored.status, DownloadStatus::Paused);
+    assert!(restored.downloaded > 0);
+
+    manager.resume_download(id.clone()).await.unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn scheduled_download_starts_after_restart() {
+    let content = test_content(300_019, 19);
+    let server = MockServer::start(content.clone(), Behavior::default()).await;
+    let dir = TempDir::new();
+    let journal = dir.path().join("downloads.json");
+    let start_at = chrono::Utc::now() + chrono::Duration::seconds(2);
+
+    let id = {
+        let manager = DownloadManager::new(test_config()).with_journal(journal.clone());
+        manager
+            .start_download(DownloadRequest {
+                start_at: Some(start_at.to_rfc3339()),
+                ..request(server.url("game.zip"), dir.file("game.zip"))
+            })
+            .await
+            .unwrap()
+    };
+
+    // Nouveau processus : le téléchargement programmé est toujours en file
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let manager = DownloadManager::new(test_config())
+        .with_journal(journal)
+        .with_event_sink(Arc::new(sender));
+    let restored = manager.get_download_progress(&id).await.unwrap();
+    assert_eq!(restored.status, DownloadStatus::Pending);
+    assert_eq!(manager.get_download_queue().await, vec![id.clone()]);
+
+    manager.start_queued_downloads().await;
+    assert_eq!(server.get_count(), 0);
+
+    // Ce que ferait la boucle des plages horaires une fois l'heure passée
+    let wait = (start_at - chrono::Utc::now()).to_std().unwrap_or_default();
+    tokio::time::sleep(wait + Duration::from_millis(100)).await;
+    manager.start_queued_downloads().await;
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn manual_queue_order_survives_a_restart() {
+    let server = MockServer::start(test_content(1_000, 21), Behavior::default()).await;
+    let dir = TempDir::new();
+    let journal = dir.path().join("downloads.json");
+    let start_at = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    let ids = {
+        let manager = DownloadManager::new(test_config()).with_journal(journal.clone());
+        let mut ids = Vec::new();
+        for name in ["base.zip", "assets.zip", "voices.zip"] {
+            let id = manager
+                .start_download(DownloadRequest {
+                    start_at: Some(start_at.to_rfc3339()),
+                    ..request(server.url(name), dir.file(name))
+                })
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        manager
+            .reorder_download_queue(vec![ids[2].clone(), ids[0].clone()])
+            .await
+            .unwrap();
+        manager.move_download_to_front(&ids[1]).await.unwrap();
+        ids
+    };
+
+    let manager = DownloadManager::new(test_config()).with_journal(journal);
+    assert_eq!(
+        manager.get_download_queue().await,
+        vec![ids[1].clone(), ids[2].clone(), ids[0].clone()]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn changed_content_restarts_from_zero() {
+    let content = test_content(600_001, 12);
+    let behavior = Behavior {
+        bytes_per_second: Some(256 * 1024),
+        ..Default::default()
+    };
+    let server = MockServer::start(content, behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    wait_for_progress(&manager, &id).await;
+    manager.pause_download(&id).await.unwrap();
+
+    // Nouvelle version publiée pendant la pause
+    let updated = test_content(650_003, 13);
+    server.replace_content(updated.clone());
+
+    manager.resume_download(id.clone()).await.unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(progress.total_size, Some(updated.len() as u64));
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), updated);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn content_changed_mid_transfer_is_hashed_from_scratch() {
+    let content = test_content(2_000_003, 16);
+    // La première connexion se fige après 1 Mo, le temps que le hachage au fil de l'eau
+    // en ait traité une partie
+    let behavior = Behavior {
+        drop_first_gets: 1,
+        drop_after_bytes: 1_000_000,
+        stall_instead_of_drop: true,
+        ..Default::default()
+    };
+    let server = MockServer::start(content, behavior).await;
+    let dir = TempDir::new();
+    // Un seul flux, et un nouveau transfert terminé avant le passage suivant du hachage
+    let config = DownloadConfig {
+        chunk_size: 4 * 1024 * 1024,
+        progress_update_interval_ms: 300,
+        read_timeout_seconds: 1,
+        ..test_config()
+    };
+    let (manager, mut events) = manager_with_events(config);
+    let updated = test_content(2_000_003, 17);
+
+    let id = manager
+        .start_download(DownloadRequest {
+            expected_sha256: Some(sha256_hex(&updated)),
+            ..request(server.url("game.zip"), dir.file("game.zip"))
+        })
+        .await
+        .unwrap();
+    wait_for_progress(&manager, &id).await;
+
+    // Nouvelle version publiée pendant le transfert : la reprise est refusée et repart de zéro
+    server.replace_content(updated.clone());
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), updated);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn expected_hash_is_verified() {
+    let content = test_content(500_009, 14);
+    let server = MockServer::start(content.clone(), Behavior::default()).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let good = manager
+        .start_download(DownloadRequest {
+            expected_sha256: Some(sha256_hex(&content).to_uppercase()),
+            ..request(server.url("good.zip"), dir.file("good.zip"))
+        })
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &good).await;
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("good.zip")).unwrap(), content);
+
+    let bad = manager
+        .start_download(DownloadRequest {
+            expected_sha256: Some(sha256_hex(b"another file")),
+            ..request(server.url("bad.zip"), dir.file("bad.zip"))
+        })
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &bad).await;
+    assert_eq!(progress.status, DownloadStatus::Failed);
+    assert_eq!(progress.failure_reason, Some(FailureReason::HashMismatch));
+    // Un fichier corrompu n'est pas laissé sur le disque
+    assert!(!std::path::Path::new(&dir.file("bad.zip")).exists());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn cached_content_is_reused_without_transfer() {
+    let content = test_content(300_007, 15);
+    let server = MockServer::start(content.clone(), Behavior::default()).await;
+    let dir = TempDir::new();
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let manager = DownloadManager::new(test_config())
+        .with_content_store(dir.path().join("objects"))
+        .with_event_sink(Arc::new(sender));
+    let sha256 = sha256_hex(&content);
+
+    let first = manager
+        .start_download(DownloadRequest {
+            expected_sha256: Some(sha256.clone()),
+            ..request(server.url("v1.zip"), dir.file("v1.zip"))
+        })
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &first).await;
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    let gets = server.get_count();
+
+    let second = manager
+        .start_download(DownloadRequest {
+            expected_sha256: Some(sha256.clone()),
+            ..request(server.url("v2.zip"), dir.file("v2.zip"))
+        })
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &second).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed);
+    assert!(progress.from_cache);
+    assert_eq!(server.get_count(), gets);
+    assert_eq!(std::fs::read(dir.file("v2.zip")).unwrap(), content);
+
+    let entries = manager.list_cache_entries().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].sha256, sha256);
+    assert_eq!(entries[0].size, content.len() as u64);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn cancelled_download_can_be_started_again() {
+    let content = test_content(800_029, 21);
+    let behavior = Behavior {
+        bytes_per_second: Some(256 * 1024),
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    wait_for_progress(&manager, &id).await;
+    manager.cancel_download(&id).await.unwrap();
+
+    // Le fichier préalloué à sa taille finale ne passe pas pour un fichier complet
+    assert!(!std::path::Path::new(&dir.file("game.zip")).exists());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    let progress = wait_for_outcome(&mut events, &id).await;
+
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn second_download_to_the_same_file_is_rejected() {
+    let content = test_content(600_011, 17);
+    let behavior = Behavior {
+        bytes_per_second: Some(256 * 1024),
+        ..Default::default()
+    };
+    let server = MockServer::start(content.clone(), behavior).await;
+    let other = MockServer::start(test_content(400_009, 18), Behavior::default()).await;
+    let dir = TempDir::new();
+    let (manager, mut events) = manager_with_events(test_config());
+
+    let id = manager
+        .start_download(request(server.url("game.zip"), dir.file("game.zip")))
+        .await
+        .unwrap();
+    wait_for_progress(&manager, &id).await;
+
+    // Le second transfert supprimerait le fichier en cours d'écriture
+    let second = manager
+        .start_download(request(other.url("game.zip"), dir.file("game.zip")))
+        .await;
+    assert!(second.is_err());
+
+    let progress = wait_for_outcome(&mut events, &id).await;
+    assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+    assert_eq!(std::fs::read(dir.file("game.zip")).unwrap(), content);
+}
+
+#[test]
+fn failed_chunk_does_not_overlap_a_stolen_range() {
+    let chunk = ChunkProgress {
+        id: 0,
+        start: 0,
+        end: 999,
+        downloaded: 0,
+        status: ChunkStatus::Pending,
+        source_url: None,
+        speed: 0,
+        error: None,
+    };
+    let scheduler = ChunkScheduler::new(vec![chunk], 1, 100);
+
+    let mut first = scheduler.next().unwrap().chunk;
+    first.downloaded = 100;
+    scheduler.advance(first.id, 100);
+
+    // Un second worker vole la seconde moitié, puis le premier échoue avec sa copie périmée
+    let stolen = scheduler.next().unwrap();
+    assert_eq!(stolen.split_from, Some(0));
+    scheduler.fail(first, "connection reset".to_string());
+
+    let failed = scheduler.take_failed();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].0.end, stolen.chunk.start - 1);
+}
+
+#[test]
+fn cache_keeps_the_most_recent_objects_within_its_budget() {
+    let dir = TempDir::new();
+    let store = ContentStore::new(dir.path().join("objects")).with_max_total_bytes(150_000);
+
+    let mut hashes = Vec::new();
+    for i in 0..2 {
+        let content = test_content(100_000 + i, 30 + i as u64);
+        let name = format!("v{}.zip", i);
+        std::fs::write(dir.file(&name), &content).unwrap();
+        hashes.push(sha256_hex(&content));
+        store.insert(std::path::Path::new(&dir.file(&name)), &hashes[i]).unwrap();
+    }
+
+    // L'objet le plus ancien a été évincé, le fichier téléchargé reste en place
+    let entries = store.list().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].sha256, hashes[1]);
+    assert!(std::path::Path::new(&dir.file("v0.zip")).exists());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn queued_downloads_respect_the_concurrency_limit() {
+    let contents: Vec<Vec<u8>> = (0..3).map(|i| test_content(150_001 + i * 1000, 20 + i as u64)).collect();
+    let dir = TempDir::new();
+    let config = DownloadConfig {
+        max_concurrent_downloads: 1,
+        ..test_config()
+    };
+    let (manager, mut events) = manager_with_events(config);
+
+    let mut servers = Vec::new();
+    let mut ids = Vec::new();
+    for (i, content) in contents.iter().enumerate() {
+        let behavior = Behavior {
+            bytes_per_second: Some(1024 * 1024),
+            ..Default::default()
+        };
+        let server = MockServer::start(content.clone(), behavior).await;
+        let name = format!("part{}.zip", i);
+        ids.push(manager.start_download(request(server.url(&name), dir.file(&name))).await.unwrap());
+        servers.push(server);
+    }
+    assert_eq!(manager.get_download_queue().await.len(), 2);
+
+    for (i, id) in ids.iter().enumerate() {
+        let progress = wait_for_outcome(&mut events, id).await;
+        assert_eq!(progress.status, DownloadStatus::Completed, "{:?}", progress.error);
+        assert_eq!(std::fs::read(dir.file(&format!("part{}.zip", i))).unwrap(), contents[i]);
+    }
+}
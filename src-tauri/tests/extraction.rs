@@ -0,0 +1,634 @@
+// Tests d'intégration de l'extraction des archives de jeu et de la mise en place de l'installation
+
+mod support;
+
+use std::io::Write;
+use std::path::Path;
+
+use ::zip::write::SimpleFileOptions;
+use ::zip::{CompressionMethod, ZipWriter};
+
+use app_lib::install_staging::{self, discard_install_rollback, has_install_rollback, rollback_install};
+use app_lib::zip::{
+    extract_archive, extract_zip_file, install_archive, ExtractionError, ExtractionLimit, ExtractionLimits,
+    RejectionReason,
+};
+use support::TempDir;
+
+// Archive non compressée : le contenu des fichiers se retrouve tel quel dans l'archive
+// Une entrée "nom -> cible" est un lien symbolique
+fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+    let mut writer = ZipWriter::new(std::fs::File::create(path).unwrap());
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, content) in entries {
+        if let Some((link, target)) = name.split_once(" -> ") {
+            writer.add_symlink(link, target, options).unwrap();
+        } else if name.ends_with('/') {
+            writer.add_directory(*name, options).unwrap();
+        } else {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content).unwrap();
+        }
+    }
+    writer.finish().unwrap();
+}
+
+// Altérer le contenu d'une entrée : son CRC-32 ne correspond plus
+fn corrupt(path: &Path, content: &[u8]) {
+    let mut archive = std::fs::read(path).unwrap();
+    let offset = archive.windows(content.len()).position(|w| w == content).unwrap();
+    archive[offset] ^= 0xFF;
+    std::fs::write(path, archive).unwrap();
+}
+
+fn read(path: &Path) -> String {
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn first_install_replaces_the_empty_install_dir() {
+    let dir = TempDir::new();
+    let archive = dir.path().join("game.zip");
+    let install = dir.path().join("install");
+    std::fs::create_dir_all(&install).unwrap();
+    write_zip(&archive, &[("bin/", b""), ("bin/game", b"v1 binary"), ("data.pak", b"v1 data")]);
+
+    extract_zip_file(archive.to_string_lossy().to_string(), install.to_string_lossy().to_string(), None, None).unwrap();
+
+    assert_eq!(read(&install.join("bin/game")), "v1 binary");
+    assert_eq!(read(&install.join("data.pak")), "v1 data");
+    assert!(!install_staging::staging_dir(&install).exists());
+    // Un dossier vide ne devient pas un point de retour
+    assert!(!has_install_rollback(install.to_string_lossy().to_string()).unwrap());
+}
+
+#[test]
+fn update_swaps_the_install_and_keeps_the_previous_version() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+
+    let v1 = dir.path().join("v1.zip");
+    write_zip(&v1, &[("game", b"v1"), ("removed_in_v2.txt", b"old")]);
+    extract_zip_file(v1.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+
+    let v2 = dir.path().join("v2.zip");
+    write_zip(&v2, &[("game", b"v2")]);
+    extract_zip_file(v2.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+
+    // La nouvelle version ne contient que les fichiers de son archive
+    assert_eq!(read(&install.join("game")), "v2");
+    assert!(!install.join("removed_in_v2.txt").exists());
+    assert!(has_install_rollback(install_arg.clone()).unwrap());
+
+    rollback_install(install_arg.clone()).unwrap();
+    assert_eq!(read(&install.join("game")), "v1");
+    assert_eq!(read(&install.join("removed_in_v2.txt")), "old");
+    assert!(!has_install_rollback(install_arg.clone()).unwrap());
+    assert!(rollback_install(install_arg).is_err());
+}
+
+#[test]
+fn failed_extraction_leaves_the_install_untouched() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+
+    let v1 = dir.path().join("v1.zip");
+    write_zip(&v1, &[("game", b"v1")]);
+    extract_zip_file(v1.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+
+    let content: &[u8] = b"v2 payload that will be corrupted";
+    let v2 = dir.path().join("v2.zip");
+    write_zip(&v2, &[("a.txt", b"first entry"), ("game", content)]);
+    corrupt(&v2, content);
+
+    assert!(extract_zip_file(v2.to_string_lossy().to_string(), install_arg.clone(), None, None).is_err());
+    assert_eq!(read(&install.join("game")), "v1");
+    assert!(!install.join("a.txt").exists());
+    assert!(!install_staging::staging_dir(&install).exists());
+    assert!(!has_install_rollback(install_arg).unwrap());
+}
+
+#[test]
+fn interrupted_swap_is_recovered() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+
+    let v1 = dir.path().join("v1.zip");
+    write_zip(&v1, &[("game", b"v1")]);
+    extract_zip_file(v1.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+
+    // Arrêt entre les deux renommages : l'installation a été écartée, la nouvelle pas encore en place
+    std::fs::rename(&install, install_staging::previous_dir(&install)).unwrap();
+
+    let v2 = dir.path().join("v2.zip");
+    write_zip(&v2, &[("game", b"v2")]);
+    extract_zip_file(v2.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+
+    assert_eq!(read(&install.join("game")), "v2");
+    rollback_install(install_arg.clone()).unwrap();
+    assert_eq!(read(&install.join("game")), "v1");
+
+    discard_install_rollback(install_arg.clone()).unwrap();
+    assert!(!has_install_rollback(install_arg).unwrap());
+}
+
+#[test]
+fn querying_the_rollback_does_not_restore_a_removed_install() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+
+    for (name, content) in [("v1.zip", b"v1"), ("v2.zip", b"v2")] {
+        let archive = dir.path().join(name);
+        write_zip(&archive, &[("game", content)]);
+        extract_zip_file(archive.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+    }
+
+    // Désinstallation : seule la version précédente subsiste
+    std::fs::remove_dir_all(&install).unwrap();
+
+    assert!(has_install_rollback(install_arg.clone()).unwrap());
+    assert!(!install.exists());
+}
+
+fn rejections(report: &app_lib::zip::ExtractionReport) -> Vec<(&str, RejectionReason)> {
+    report.rejected.iter().map(|r| (r.name.as_str(), r.reason.clone())).collect()
+}
+
+#[test]
+fn unsafe_entry_names_are_rejected_and_reported() {
+    let dir = TempDir::new();
+    let archive = dir.path().join("game.zip");
+    let install = dir.path().join("games").join("install");
+    write_zip(
+        &archive,
+        &[
+            ("../escape.txt", b"x"),
+            ("bin/../../escape.txt", b"x"),
+            ("/etc/escape.txt", b"x"),
+            ("C:\\escape.txt", b"x"),
+            ("\\\\server\\share\\escape.txt", b"x"),
+            ("bin\\..\\..\\escape.txt", b"x"),
+            ("bin/./../data/ok.txt", b"kept"),
+            ("bin\\game", b"binary"),
+        ],
+    );
+
+    let report = extract_zip_file(archive.to_string_lossy().to_string(), install.to_string_lossy().to_string(), None, None).unwrap();
+
+    assert_eq!(
+        rejections(&report),
+        vec![
+            ("../escape.txt", RejectionReason::ParentTraversal),
+            ("bin/../../escape.txt", RejectionReason::ParentTraversal),
+            ("/etc/escape.txt", RejectionReason::AbsolutePath),
+            ("C:\\escape.txt", RejectionReason::AbsolutePath),
+            ("\\\\server\\share\\escape.txt", RejectionReason::AbsolutePath),
+            ("bin\\..\\..\\escape.txt", RejectionReason::ParentTraversal),
+        ]
+    );
+    assert_eq!(report.files_extracted, 2);
+    // Les noms restés dans l'archive sont normalisés
+    assert_eq!(read(&install.join("data/ok.txt")), "kept");
+    assert_eq!(read(&install.join("bin/game")), "binary");
+    assert!(!dir.path().join("escape.txt").exists());
+    assert!(!dir.path().join("games").join("escape.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_kept_only_inside_the_install() {
+    let dir = TempDir::new();
+    let archive = dir.path().join("game.zip");
+    let install = dir.path().join("install");
+    std::fs::write(dir.path().join("secret.txt"), "outside").unwrap();
+    write_zip(
+        &archive,
+        &[
+            ("lib/libgame.so.1", b"library"),
+            ("lib/libgame.so -> libgame.so.1", b""),
+            ("current -> lib", b""),
+            ("escape -> ../secret.txt", b""),
+            ("absolute -> /etc/passwd", b""),
+            ("lib/absolute -> /etc/passwd", b""),
+            ("here -> .", b""),
+            // Remonte depuis la cible d'un autre lien, pas depuis son dossier
+            ("sneaky -> here/../secret.txt", b""),
+            // Écrire à travers un lien extrait plus tôt
+            ("current/injected.txt", b"x"),
+        ],
+    );
+
+    let report = extract_zip_file(archive.to_string_lossy().to_string(), install.to_string_lossy().to_string(), None, None).unwrap();
+
+    assert_eq!(
+        rejections(&report),
+        vec![
+            ("escape", RejectionReason::SymlinkOutsideTarget),
+            ("absolute", RejectionReason::SymlinkOutsideTarget),
+            ("lib/absolute", RejectionReason::SymlinkOutsideTarget),
+            ("sneaky", RejectionReason::SymlinkOutsideTarget),
+            ("current/injected.txt", RejectionReason::ThroughSymlink),
+        ]
+    );
+    assert_eq!(report.symlinks_created, 3);
+    assert_eq!(read(&install.join("lib/libgame.so")), "library");
+    assert_eq!(read(&install.join("current/libgame.so.1")), "library");
+    assert!(std::fs::symlink_metadata(install.join("escape")).is_err());
+    assert!(!install.join("lib/injected.txt").exists());
+}
+
+// Entrée unique très compressible (des zéros)
+fn write_compressible_zip(path: &Path, size: usize) {
+    let mut writer = ZipWriter::new(std::fs::File::create(path).unwrap());
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    writer.start_file("zeros.bin", options).unwrap();
+    writer.write_all(&vec![0u8; size]).unwrap();
+    writer.finish().unwrap();
+}
+
+// Annoncer une taille décompressée mensongère dans les en-têtes local et central
+fn patch_declared_size(path: &Path, size: u32) {
+    let mut archive = std::fs::read(path).unwrap();
+    for (signature, offset) in [(b"PK\x03\x04", 22), (b"PK\x01\x02", 24)] {
+        let header = archive.windows(4).position(|w| w == signature).unwrap();
+        archive[header + offset..header + offset + 4].copy_from_slice(&size.to_le_bytes());
+    }
+    std::fs::write(path, archive).unwrap();
+}
+
+fn exceeded_limit<T: std::fmt::Debug>(result: Result<T, ExtractionError>) -> ExtractionLimit {
+    match result {
+        Err(ExtractionError::LimitExceeded { limit, .. }) => limit,
+        other => panic!("expected a limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn extraction_limits_are_checked_before_writing() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+    let extract = |archive: &Path, limits: ExtractionLimits| {
+        install_archive(&archive.to_string_lossy(), &install_arg, &limits, None, |_, _, _| {})
+    };
+
+    let many = dir.path().join("many.zip");
+    write_zip(&many, &[("a", b"1"), ("b", b"2"), ("c", b"3"), ("d", b"4")]);
+    let limits = ExtractionLimits {
+        max_entries: 3,
+        ..Default::default()
+    };
+    assert_eq!(exceeded_limit(extract(&many, limits)), ExtractionLimit::Entries);
+
+    let long_name = "d/".repeat(40) + "file";
+    let deep = dir.path().join("deep.zip");
+    write_zip(&deep, &[(long_name.as_str(), b"x")]);
+    let limits = ExtractionLimits {
+        max_path_length: 64,
+        ..Default::default()
+    };
+    assert_eq!(exceeded_limit(extract(&deep, limits)), ExtractionLimit::PathLength);
+
+    let large = dir.path().join("large.zip");
+    write_zip(&large, &[("a", &[7u8; 600]), ("b", &[7u8; 600])]);
+    let limits = ExtractionLimits {
+        max_total_bytes: 1000,
+        ..Default::default()
+    };
+    assert_eq!(exceeded_limit(extract(&large, limits)), ExtractionLimit::TotalBytes);
+
+    let bomb = dir.path().join("bomb.zip");
+    write_compressible_zip(&bomb, 8 * 1024 * 1024);
+    let limits = ExtractionLimits {
+        max_compression_ratio: 100,
+        ..Default::default()
+    };
+    assert_eq!(exceeded_limit(extract(&bomb, limits)), ExtractionLimit::CompressionRatio);
+
+    // Les limites par défaut acceptent ces archives, et la commande nomme la limite dépassée
+    assert!(extract(&bomb, ExtractionLimits::default()).is_ok());
+    let error = extract_zip_file(
+        many.to_string_lossy().to_string(),
+        install_arg.clone(),
+        Some(ExtractionLimits {
+            max_entries: 3,
+            ..Default::default()
+        }),
+        None,
+    )
+    .unwrap_err();
+    assert!(error.contains("entry count"), "{}", error);
+    assert_eq!(read(&install.join("zeros.bin")).len(), 8 * 1024 * 1024);
+}
+
+#[test]
+fn understated_sizes_are_caught_while_extracting() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let bomb = dir.path().join("bomb.zip");
+    write_compressible_zip(&bomb, 4 * 1024 * 1024);
+    patch_declared_size(&bomb, 1000);
+
+    let limits = ExtractionLimits {
+        max_total_bytes: 2 * 1024 * 1024,
+        ..Default::default()
+    };
+    let result = install_archive(&bomb.to_string_lossy(), &install.to_string_lossy(), &limits, None, |_, _, _| {});
+
+    assert_eq!(exceeded_limit(result), ExtractionLimit::TotalBytes);
+    assert!(!install.exists());
+    assert!(!install_staging::staging_dir(&install).exists());
+}
+
+// Archive dont chaque entrée porte des droits Unix ; toutes datées du 17 mai 2020 à 12:30:00
+#[cfg(unix)]
+fn write_zip_with_modes(path: &Path, entries: &[(&str, u32)]) {
+    let mut writer = ZipWriter::new(std::fs::File::create(path).unwrap());
+    let modified = ::zip::DateTime::from_date_and_time(2020, 5, 17, 12, 30, 0).unwrap();
+    for (name, mode) in entries {
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .last_modified_time(modified)
+            .unix_permissions(*mode);
+        if name.ends_with('/') {
+            writer.add_directory(*name, options).unwrap();
+        } else {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(name.as_bytes()).unwrap();
+        }
+    }
+    writer.finish().unwrap();
+}
+
+#[cfg(unix)]
+fn mode(path: &Path) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o7777
+}
+
+#[cfg(unix)]
+#[test]
+fn stored_permissions_and_times_are_restored() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+    let archive = dir.path().join("game.zip");
+    write_zip_with_modes(
+        &archive,
+        &[
+            ("bin/", 0o750),
+            ("bin/run.sh", 0o755),
+            ("config.ini", 0o600),
+            ("readonly.txt", 0o444),
+            ("unreadable", 0o200),
+            ("locked/", 0o500),
+            ("locked/data", 0o644),
+        ],
+    );
+
+    extract_zip_file(archive.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+
+    assert_eq!(mode(&install.join("bin")), 0o750);
+    assert_eq!(mode(&install.join("bin/run.sh")), 0o755);
+    assert_eq!(mode(&install.join("config.ini")), 0o600);
+    assert_eq!(mode(&install.join("readonly.txt")), 0o444);
+    // Le propriétaire garde la lecture des fichiers et la main sur les dossiers
+    assert_eq!(mode(&install.join("unreadable")), 0o600);
+    assert_eq!(mode(&install.join("locked")), 0o700);
+
+    let expected = chrono::NaiveDate::from_ymd_opt(2020, 5, 17)
+        .unwrap()
+        .and_hms_opt(12, 30, 0)
+        .unwrap()
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .unwrap();
+    let modified = std::fs::metadata(install.join("config.ini")).unwrap().modified().unwrap();
+    assert_eq!(chrono::DateTime::<chrono::Local>::from(modified), expected);
+
+    // Ces droits n'empêchent ni la mise à jour ni le retour en arrière
+    extract_zip_file(archive.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+    rollback_install(install_arg.clone()).unwrap();
+    discard_install_rollback(install_arg).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn declared_executable_is_made_executable() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+    let archive = dir.path().join("game.zip");
+    write_zip_with_modes(&archive, &[("bin/", 0o755), ("bin/game", 0o644), ("notes.txt", 0o640)]);
+    let extract = |executable: &str| {
+        extract_zip_file(
+            archive.to_string_lossy().to_string(),
+            install_arg.clone(),
+            None,
+            Some(executable.to_string()),
+        )
+    };
+
+    extract("bin/game").unwrap();
+    assert_eq!(mode(&install.join("bin/game")), 0o755);
+    assert_eq!(mode(&install.join("notes.txt")), 0o640);
+
+    // Un exécutable absent n'empêche pas l'installation ; un chemin hors de l'installation, si
+    extract("bin/missing").unwrap();
+    assert_eq!(mode(&install.join("bin/game")), 0o644);
+    assert!(extract("../escape").is_err());
+    assert_eq!(mode(&install.join("bin/game")), 0o644);
+}
+
+// Entrées communes aux tests multi-formats : (nom, mode, contenu), avec les mêmes conventions
+// que `write_zip` pour les dossiers et les liens
+const PACKAGE: &[(&str, u32, &[u8])] = &[
+    ("bin/", 0o755, b""),
+    ("bin/game", 0o755, b"binary"),
+    ("../escape.txt", 0o644, b"x"),
+    ("data/level.pak", 0o644, b"level data"),
+    ("current -> data", 0o777, b""),
+];
+
+// Le 17 mai 2020 à 12:30:00 UTC
+const PACKAGE_MTIME: u64 = 1_589_718_600;
+
+fn tar_bytes(entries: &[(&str, u32, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, mode, content) in entries {
+        let mut header = tar::Header::new_gnu();
+        let (name, link) = match name.split_once(" -> ") {
+            Some((link, target)) => (link, Some(target)),
+            None => (*name, None),
+        };
+        let entry_type = match link {
+            Some(_) => tar::EntryType::Symlink,
+            None if name.ends_with('/') => tar::EntryType::Directory,
+            None => tar::EntryType::Regular,
+        };
+        // Nom écrit tel quel : `set_path` refuserait les noms dangereux
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_mode(*mode);
+        header.set_mtime(PACKAGE_MTIME);
+        header.set_size(content.len() as u64);
+        if let Some(target) = link {
+            header.set_link_name(target).unwrap();
+        }
+        header.set_cksum();
+        builder.append(&header, *content).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+// Archive 7z solide : tous les contenus dans un même bloc compressé
+fn write_7z(path: &Path, entries: &[(&str, u32, &[u8])]) {
+    let mut writer = sevenz_rust::SevenZWriter::create(path).unwrap();
+    let mut solid_entries = Vec::new();
+    let mut contents = Vec::new();
+    for (name, mode, content) in entries {
+        let (name, content, file_type): (&str, &[u8], u32) = match name.split_once(" -> ") {
+            Some((link, target)) => (link, target.as_bytes(), 0o120000),
+            None => (name, content, 0o100000),
+        };
+        let mut entry = sevenz_rust::SevenZArchiveEntry::new();
+        entry.name = name.trim_end_matches('/').to_string();
+        entry.is_directory = name.ends_with('/');
+        entry.has_stream = !entry.is_directory;
+        entry.has_windows_attributes = true;
+        entry.windows_attributes = 0x8000 | ((file_type | mode) << 16);
+        entry.has_last_modified_date = true;
+        entry.last_modified_date = (std::time::UNIX_EPOCH + std::time::Duration::from_secs(PACKAGE_MTIME))
+            .try_into()
+            .unwrap();
+        if entry.is_directory {
+            writer.push_archive_entry::<&[u8]>(entry, None).unwrap();
+        } else {
+            solid_entries.push(entry);
+            contents.push(sevenz_rust::SourceReader::from(content));
+        }
+    }
+    writer
+        .push_archive_entries(solid_entries, sevenz_rust::SeqReader::new(contents))
+        .unwrap();
+    writer.finish().unwrap();
+}
+
+#[test]
+fn every_archive_format_is_detected_from_its_content() {
+    let dir = TempDir::new();
+    let tar = tar_bytes(PACKAGE);
+    let packages = [
+        ("game.tar", tar.clone()),
+        ("game.tar.gz", {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&tar).unwrap();
+            encoder.finish().unwrap()
+        }),
+        ("game.tar.xz", {
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+            encoder.write_all(&tar).unwrap();
+            encoder.finish().unwrap()
+        }),
+        ("game.tar.zst", zstd::encode_all(tar.as_slice(), 3).unwrap()),
+    ];
+    let mut archives = Vec::new();
+    for (name, bytes) in packages {
+        // Le nom du fichier ne compte pas : seul le contenu désigne le format
+        let path = dir.path().join(format!("{}.download", name));
+        std::fs::write(&path, bytes).unwrap();
+        archives.push(path);
+    }
+    let seven_z = dir.path().join("game.7z.download");
+    write_7z(&seven_z, PACKAGE);
+    archives.push(seven_z);
+
+    for archive in archives {
+        let install = dir.path().join("installs").join(archive.file_name().unwrap());
+        let report = extract_archive(
+            archive.to_string_lossy().to_string(),
+            install.to_string_lossy().to_string(),
+            None,
+            None,
+        )
+        .unwrap_or_else(|e| panic!("{}: {}", archive.display(), e));
+
+        let name = archive.display();
+        assert_eq!(rejections(&report)[0], ("../escape.txt", RejectionReason::ParentTraversal), "{}", name);
+        assert_eq!(report.files_extracted, 2, "{}", name);
+        assert_eq!(read(&install.join("bin/game")), "binary", "{}", name);
+        assert_eq!(read(&install.join("data/level.pak")), "level data", "{}", name);
+        assert!(!dir.path().join("installs").join("escape.txt").exists(), "{}", name);
+
+        let modified = std::fs::metadata(install.join("data/level.pak")).unwrap().modified().unwrap();
+        assert_eq!(
+            modified.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+            PACKAGE_MTIME,
+            "{}",
+            name
+        );
+
+        #[cfg(unix)]
+        {
+            assert_eq!(report.symlinks_created, 1, "{}", name);
+            assert_eq!(read(&install.join("current/level.pak")), "level data", "{}", name);
+            assert_eq!(mode(&install.join("bin/game")), 0o755, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn unknown_archive_formats_are_refused() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+    let v1 = dir.path().join("v1.zip");
+    write_zip(&v1, &[("game", b"v1")]);
+    extract_zip_file(v1.to_string_lossy().to_string(), install_arg.clone(), None, None).unwrap();
+
+    let unknown = dir.path().join("game.rar");
+    std::fs::write(&unknown, b"Rar!\x1a\x07\x00 not supported").unwrap();
+    let error = extract_archive(unknown.to_string_lossy().to_string(), install_arg, None, None).unwrap_err();
+
+    assert!(error.contains("Unsupported archive format"), "{}", error);
+    assert_eq!(read(&install.join("game")), "v1");
+}
+
+#[test]
+fn solid_archives_are_limited_by_their_overall_ratio() {
+    let dir = TempDir::new();
+    let install = dir.path().join("install");
+    let install_arg = install.to_string_lossy().to_string();
+    let zeros = vec![0u8; 8 * 1024 * 1024];
+    let bomb = dir.path().join("zeros.tar.zst");
+    std::fs::write(&bomb, zstd::encode_all(tar_bytes(&[("zeros.bin", 0o644, &zeros)]).as_slice(), 19).unwrap()).unwrap();
+    let extract = |limits: ExtractionLimits| {
+        let mut progress = Vec::new();
+        install_archive(&bomb.to_string_lossy(), &install_arg, &limits, None, |done, total, _| {
+            progress.push((done, total))
+        })
+        .map(|_| progress)
+    };
+
+    // Des zéros compressés par zstd dépassent largement le taux par défaut, mesuré sur toute l'archive
+    assert_eq!(exceeded_limit(extract(ExtractionLimits::default())), ExtractionLimit::CompressionRatio);
+    assert!(!install.exists());
+
+    let unbounded_ratio = ExtractionLimits {
+        max_compression_ratio: u64::MAX,
+        ..Default::default()
+    };
+    let limits = ExtractionLimits {
+        max_total_bytes: 1024 * 1024,
+        ..unbounded_ratio.clone()
+    };
+    assert_eq!(exceeded_limit(extract(limits)), ExtractionLimit::TotalBytes);
+
+    // Le parcours préalable donne le nombre d'entrées à la progression
+    assert_eq!(extract(unbounded_ratio).unwrap(), vec![(0, 1), (1, 1)]);
+    assert_eq!(std::fs::read(install.join("zeros.bin")).unwrap(), zeros);
+}
@@ -0,0 +1,373 @@
+// Serveur HTTP de test en mémoire, pour exercer le gestionnaire de téléchargements sans réseau.
+// Partagé par plusieurs fichiers de test : chacun n'en utilise qu'une partie.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
+
+use app_lib::download_events::DownloadEvent;
+use app_lib::download_manager::{DownloadConfig, DownloadProgress, NetworkConfig};
+
+// Taille des écritures du serveur ; une coupure ou un ralentissement s'applique par tranche
+const WRITE_SLICE: usize = 16 * 1024;
+
+/// Comportement simulé du serveur
+#[derive(Debug, Clone)]
+pub struct Behavior {
+    // Répondre 206 aux requêtes Range et annoncer Accept-Ranges
+    pub ranges: bool,
+    // Envoyer Content-Length ; sinon le corps se termine à la fermeture de la connexion
+    pub content_length: bool,
+    // Les N premières requêtes GET reçoivent `fail_status` (503 par défaut)
+    pub fail_first_gets: usize,
+    pub fail_status: &'static str,
+    // Valeur du header Retry-After joint à ces réponses en erreur
+    pub retry_after: Option<String>,
+    // Les N premières réponses GET réussies sont coupées après `drop_after_bytes` octets
+    pub drop_first_gets: usize,
+    pub drop_after_bytes: usize,
+    // Garder ces connexions ouvertes sans plus rien envoyer au lieu de les fermer
+    pub stall_instead_of_drop: bool,
+    // Débit maximal par connexion (pair lent)
+    pub bytes_per_second: Option<u64>,
+}
+
+impl Default for Behavior {
+    fn default() -> Self {
+        Self {
+            ranges: true,
+            content_length: true,
+            fail_first_gets: 0,
+            fail_status: "503 Service Unavailable",
+            retry_after: None,
+            drop_first_gets: 0,
+            drop_after_bytes: 0,
+            stall_instead_of_drop: false,
+            bytes_per_second: None,
+        }
+    }
+}
+
+struct ServerState {
+    content: Arc<Vec<u8>>,
+    etag: String,
+    behavior: Behavior,
+    gets: usize,
+    served_gets: usize,
+    // Début de chaque plage demandée
+    range_starts: Vec<u64>,
+    // Header Authorization de chaque requête qui en portait un
+    authorizations: Vec<String>,
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start(content: Vec<u8>, behavior: Behavior) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(ServerState {
+            etag: etag_for(&content),
+            content: Arc::new(content),
+            behavior,
+            gets: 0,
+            served_gets: 0,
+            range_starts: Vec::new(),
+            authorizations: Vec::new(),
+        }));
+
+        let accept_state = Arc::clone(&state);
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = Arc::clone(&accept_state);
+                tokio::spawn(async move {
+                    // Une connexion coupée par le client n'est pas une erreur du test
+                    let _ = handle_connection(stream, state).await;
+                });
+            }
+        });
+
+        Self { addr, state, task }
+    }
+
+    pub fn url(&self, name: &str) -> String {
+        format!("http://{}/{}", self.addr, name)
+    }
+
+    // Publier un nouveau contenu (nouvel ETag), comme une nouvelle version de l'archive
+    pub fn replace_content(&self, content: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        state.etag = etag_for(&content);
+        state.content = Arc::new(content);
+    }
+
+    pub fn get_count(&self) -> usize {
+        self.state.lock().unwrap().gets
+    }
+
+    pub fn range_starts(&self) -> Vec<u64> {
+        self.state.lock().unwrap().range_starts.clone()
+    }
+
+    pub fn authorizations(&self) -> Vec<String> {
+        self.state.lock().unwrap().authorizations.clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+struct Reply {
+    status: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: Arc<Vec<u8>>,
+    start: usize,
+    end: usize, // exclue
+    drop_after: Option<usize>,
+    stall: bool,
+    bytes_per_second: Option<u64>,
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<ServerState>>) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.split();
+    let mut reader = BufReader::new(reader);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let method = request_line.split_whitespace().next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    if let Some(authorization) = headers.get("authorization") {
+        state.lock().unwrap().authorizations.push(authorization.clone());
+    }
+    let reply = build_reply(&method, &headers, &state);
+
+    let mut head = format!("HTTP/1.1 {}\r\nConnection: close\r\n", reply.status);
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes()).await?;
+
+    if method == "HEAD" {
+        return writer.flush().await;
+    }
+
+    let end = match reply.drop_after {
+        Some(limit) => reply.end.min(reply.start + limit),
+        None => reply.end,
+    };
+    let mut position = reply.start;
+    while position < end {
+        let slice_end = (position + WRITE_SLICE).min(end);
+        writer.write_all(&reply.body[position..slice_end]).await?;
+        writer.flush().await?;
+
+        if let Some(bytes_per_second) = reply.bytes_per_second {
+            let pause = (slice_end - position) as f64 / bytes_per_second as f64;
+            tokio::time::sleep(Duration::from_secs_f64(pause)).await;
+        }
+        position = slice_end;
+    }
+
+    if reply.stall && end < reply.end {
+        // Le client doit abandonner de lui-même
+        std::future::pending::<()>().await;
+    }
+
+    // Coupure : fermer sans envoyer la suite annoncée
+    writer.shutdown().await
+}
+
+fn build_reply(method: &str, headers: &HashMap<String, String>, state: &Mutex<ServerState>) -> Reply {
+    let mut state = state.lock().unwrap();
+    let behavior = state.behavior.clone();
+    let content = Arc::clone(&state.content);
+    let etag = state.etag.clone();
+    let len = content.len();
+
+    let mut reply = Reply {
+        status: "200 OK",
+        headers: vec![("ETag", etag.clone())],
+        body: Arc::clone(&content),
+        start: 0,
+        end: len,
+        drop_after: None,
+        stall: behavior.stall_instead_of_drop,
+        bytes_per_second: behavior.bytes_per_second,
+    };
+    if behavior.ranges {
+        reply.headers.push(("Accept-Ranges", "bytes".to_string()));
+    }
+
+    if method == "GET" {
+        let index = state.gets;
+        state.gets += 1;
+        if index < behavior.fail_first_gets {
+            let mut headers = vec![("Content-Length", "0".to_string())];
+            if let Some(retry_after) = behavior.retry_after {
+                headers.push(("Retry-After", retry_after));
+            }
+            return Reply {
+                status: behavior.fail_status,
+                headers,
+                body: content,
+                start: 0,
+                end: 0,
+                drop_after: None,
+                stall: false,
+                bytes_per_second: None,
+            };
+        }
+
+        if state.served_gets < behavior.drop_first_gets {
+            reply.drop_after = Some(behavior.drop_after_bytes);
+        }
+        state.served_gets += 1;
+
+        // If-Range : une plage n'est servie que si la ressource n'a pas changé
+        let range = headers
+            .get("range")
+            .filter(|_| behavior.ranges)
+            .filter(|_| headers.get("if-range").map_or(true, |tag| *tag == etag))
+            .and_then(|range| parse_range(range, len));
+
+        if let Some((start, end)) = range {
+            state.range_starts.push(start as u64);
+            reply.status = "206 Partial Content";
+            reply.headers.push(("Content-Range", format!("bytes {}-{}/{}", start, end, len)));
+            reply.headers.push(("Content-Length", (end + 1 - start).to_string()));
+            reply.start = start;
+            reply.end = end + 1;
+            return reply;
+        }
+    }
+
+    if behavior.content_length {
+        reply.headers.push(("Content-Length", len.to_string()));
+    }
+    reply
+}
+
+// "bytes=a-b" ou "bytes=a-" ; fin incluse, bornée à la taille du contenu
+fn parse_range(value: &str, len: usize) -> Option<(usize, usize)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    let start: usize = start.trim().parse().ok()?;
+    let end = match end.trim() {
+        "" => len.checked_sub(1)?,
+        end => end.parse::<usize>().ok()?.min(len.checked_sub(1)?),
+    };
+    (start <= end).then_some((start, end))
+}
+
+fn etag_for(content: &[u8]) -> String {
+    format!("\"{}\"", &sha256_hex(content)[..16])
+}
+
+pub fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+// Contenu pseudo-aléatoire reproductible : un décalage d'octets se voit dans la comparaison
+pub fn test_content(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 24) as u8
+        })
+        .collect()
+}
+
+// Réglages rapides : petits chunks et délais courts
+pub fn test_config() -> DownloadConfig {
+    DownloadConfig {
+        max_concurrent_chunks: 4,
+        chunk_size: 64 * 1024,
+        max_retries: 4,
+        retry_delay_ms: 10,
+        max_retry_delay_ms: 100,
+        connect_timeout_seconds: 5,
+        read_timeout_seconds: 5,
+        total_timeout_seconds: None,
+        progress_update_interval_ms: 20,
+        max_bytes_per_second: None,
+        max_concurrent_downloads: 2,
+        spread_chunks_across_mirrors: false,
+        disk_space_margin_bytes: 0,
+        network: NetworkConfig::default(),
+    }
+}
+
+/// Répertoire temporaire supprimé à la fin du test
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("lysandra-download-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn file(&self, name: &str) -> String {
+        self.0.join(name).to_string_lossy().to_string()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// Attendre la fin (réussie ou non) d'un téléchargement
+pub async fn wait_for_outcome(events: &mut UnboundedReceiver<DownloadEvent>, download_id: &str) -> DownloadProgress {
+    tokio::time::timeout(Duration::from_secs(60), async {
+        loop {
+            match events.recv().await.expect("event channel closed") {
+                DownloadEvent::Completed(progress) | DownloadEvent::Failed(progress) if progress.id == download_id => {
+                    return progress;
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("download did not finish in time")
+}
@@ -65,7 +65,8 @@ const DownloadItem: React.FC<DownloadItemProps> = ({
       <div className="mb-4">
         <div className="mb-2 flex justify-between text-sm text-gray-600 dark:text-gray-300">
           <span>
-            {formatBytes(download.downloaded)} / {formatBytes(download.total_size)}
+            {formatBytes(download.downloaded)} /{' '}
+            {download.total_size !== null ? formatBytes(download.total_size) : '?'}
           </span>
           <span>{download.percentage.toFixed(1)}%</span>
         </div>
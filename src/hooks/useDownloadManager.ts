@@ -9,6 +9,7 @@ import {
   DownloadProgressEvent,
   DownloadCompletedEvent,
   DownloadFailedEvent,
+  DownloadGroupCompletedEvent,
 } from '../types/download'
 
 export const useDownloadManager = (): DownloadManagerAPI & {
@@ -91,8 +92,23 @@ export const useDownloadManager = (): DownloadManagerAPI & {
           refreshStats() // Mettre à jour les statistiques
         })
 
+        // Les membres d'un groupe ne signalent pas leur propre fin : recharger la liste
+        // quand le groupe entier est terminé
+        const unlistenGroupCompleted = await listen<DownloadGroupCompletedEvent>(
+          'download-group-completed',
+          () => {
+            refreshDownloads()
+            refreshStats()
+          },
+        )
+
         // Sauvegarder les fonctions de désabonnement
-        unlistenFunctions.current = [unlistenProgress, unlistenCompleted, unlistenFailed]
+        unlistenFunctions.current = [
+          unlistenProgress,
+          unlistenCompleted,
+          unlistenFailed,
+          unlistenGroupCompleted,
+        ]
       } catch (err) {
         console.error('Failed to setup download listeners:', err)
         setError('Failed to setup download listeners')
@@ -107,7 +123,7 @@ export const useDownloadManager = (): DownloadManagerAPI & {
         if (unlisten) unlisten()
       })
     }
-  }, [updateDownload, refreshStats])
+  }, [updateDownload, refreshDownloads, refreshStats])
 
   // Charger les données initiales
   useEffect(() => {
@@ -58,7 +58,7 @@ export const useGameDownload = () => {
                 percentage: download.percentage,
                 speed: download.speed,
                 downloadedBytes: download.downloaded,
-                totalBytes: download.total_size,
+                totalBytes: download.total_size ?? 0,
                 error: download.error,
               })
 
@@ -86,8 +86,8 @@ export const useGameDownload = () => {
                 status: 'Completed',
                 percentage: 100,
                 speed: 0,
-                downloadedBytes: download.total_size,
-                totalBytes: download.total_size,
+                downloadedBytes: download.total_size ?? download.downloaded,
+                totalBytes: download.total_size ?? download.downloaded,
               })
 
               return updated
@@ -115,7 +115,7 @@ export const useGameDownload = () => {
                 percentage: download.percentage,
                 speed: 0,
                 downloadedBytes: download.downloaded,
-                totalBytes: download.total_size,
+                totalBytes: download.total_size ?? 0,
                 error: download.error,
               })
 
@@ -6,21 +6,32 @@ export type DownloadStatus =
   | 'Failed'
   | 'Cancelled'
 
+export type FailureReason = 'Transfer' | 'HashMismatch' | 'InsufficientSpace'
+
 export type ChunkStatus = 'Pending' | 'Downloading' | 'Completed' | 'Failed'
 
+export type FailedRange = {
+  start: number
+  end: number // inclus
+  error: string
+}
+
 export type ChunkProgress = {
   id: number
   start: number
   end: number
   downloaded: number
   status: ChunkStatus
+  source_url?: string
+  speed: number // bytes/sec
+  error?: string // dernière erreur, effacée quand le chunk se termine
 }
 
 export type DownloadProgress = {
   id: string
   url: string
   file_path: string
-  total_size: number
+  total_size: number | null // null : taille inconnue
   downloaded: number
   speed: number // bytes/sec
   percentage: number
@@ -30,11 +41,50 @@ export type DownloadProgress = {
   created_at: string
   started_at?: string
   completed_at?: string
+  eta_seconds?: number
+  etag?: string
+  last_modified?: string
+  expected_sha256?: string
+  failure_reason?: FailureReason
+  failed_ranges: FailedRange[]
+  priority: number
+  mirrors: string[]
+  validator_url?: string
+  group_id?: string
+  from_cache: boolean // servi par le cache, sans transfert
+  start_at?: string // démarrage programmé (RFC 3339)
+  overrides: DownloadOverrides
+}
+
+export type DownloadGroupProgress = {
+  id: string
+  name?: string
+  download_ids: string[]
+  total_size: number | null // null : taille d'un membre inconnue
+  downloaded: number
+  speed: number // bytes/sec
+  eta_seconds?: number
+  percentage: number
+  status: DownloadStatus
+  created_at: string
+  completed_at?: string
+}
+
+export type DownloadRequest = {
+  url: string
+  file_path: string
+  mirrors?: string[]
+  headers?: Record<string, string> // envoyés à l'origine de `url` seulement, non conservés après un redémarrage
+  expected_sha256?: string
+  priority?: number
+  start_at?: string // RFC 3339
+  overrides?: DownloadOverrides
 }
 
 export type DownloadStats = {
   total_downloads: number
   active_downloads: number
+  queued_downloads: number
   completed_downloads: number
   failed_downloads: number
   total_downloaded_bytes: number
@@ -45,19 +95,88 @@ export type DownloadConfig = {
   max_concurrent_chunks: number
   chunk_size: number
   max_retries: number
-  retry_delay_ms: number
-  timeout_seconds: number
+  retry_delay_ms: number // premier délai, doublé à chaque échec (avec jitter)
+  max_retry_delay_ms: number
+  connect_timeout_seconds: number
+  read_timeout_seconds: number // délai sans données avant de relancer
+  total_timeout_seconds?: number // par requête ; absent : aucune limite
   progress_update_interval_ms: number
+  max_bytes_per_second?: number
+  max_concurrent_downloads: number
+  spread_chunks_across_mirrors: boolean
+  disk_space_margin_bytes: number
+  network: NetworkConfig
+}
+
+// Réglages propres à un téléchargement ; un champ absent reprend la configuration globale
+export type DownloadOverrides = {
+  max_concurrent_chunks?: number
+  chunk_size?: number
+  max_retries?: number
+  retry_delay_ms?: number
+  read_timeout_seconds?: number
+  total_timeout_seconds?: number
+}
+
+export type NetworkConfig = {
+  proxy_url?: string // http://, https://, socks5://
+  no_proxy: string[]
+  ca_certificates: string[] // fichiers PEM
+}
+
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun'
+
+// Plage horaire récurrente en heure locale ; fin < début : se termine le lendemain
+export type ScheduleWindow = {
+  start: string // HH:MM
+  end: string // HH:MM
+  days?: Weekday[] // vide : tous les jours
+}
+
+export type OutsideWindowPolicy =
+  | 'Pause'
+  | { Throttle: { bytes_per_second: number } }
+
+export type DownloadSchedule = {
+  windows: ScheduleWindow[] // vide : aucune restriction
+  outside_windows: OutsideWindowPolicy
+  queue_start_at?: string // RFC 3339
+}
+
+// Fichier du cache adressé par contenu (cache/objects)
+export type CacheEntry = {
+  sha256: string
+  path: string
+  size: number
+  last_used: string
+}
+
+export type CachePruneReport = {
+  removed: CacheEntry[]
+  freed_bytes: number
+  remaining_bytes: number
 }
 
 // Événements émis par Tauri
 export type DownloadProgressEvent = DownloadProgress
 export type DownloadCompletedEvent = DownloadProgress
 export type DownloadFailedEvent = DownloadProgress
+export type DownloadGroupProgressEvent = DownloadGroupProgress
+export type DownloadGroupCompletedEvent = DownloadGroupProgress
+export type DownloadGroupFailedEvent = DownloadGroupProgress
 
 // API pour les commandes Tauri
 export interface DownloadManagerAPI {
-  startDownload(url: string, filePath: string): Promise<string>
+  startDownload(
+    url: string,
+    filePath: string,
+    mirrors?: string[],
+    headers?: Record<string, string>,
+    expectedSha256?: string,
+    priority?: number,
+    startAt?: string,
+    overrides?: DownloadOverrides,
+  ): Promise<string>
   pauseDownload(downloadId: string): Promise<void>
   resumeDownload(downloadId: string): Promise<void>
   cancelDownload(downloadId: string): Promise<void>
@@ -66,6 +185,20 @@ export interface DownloadManagerAPI {
   getAllDownloads(): Promise<DownloadProgress[]>
   cleanupCompletedDownloads(): Promise<void>
   getDownloadStats(): Promise<DownloadStats>
+  startDownloadGroup(files: DownloadRequest[], name?: string): Promise<string>
+  getDownloadGroup(groupId: string): Promise<DownloadGroupProgress | null>
+  getAllDownloadGroups(): Promise<DownloadGroupProgress[]>
+  pauseDownloadGroup(groupId: string): Promise<void>
+  resumeDownloadGroup(groupId: string): Promise<void>
+  cancelDownloadGroup(groupId: string): Promise<void>
+  removeDownloadGroup(groupId: string): Promise<void>
+  getDownloadConfig(): Promise<DownloadConfig>
+  setDownloadConfig(config: DownloadConfig): Promise<void>
+  getDownloadSchedule(): Promise<DownloadSchedule>
+  setDownloadSchedule(schedule: DownloadSchedule): Promise<void>
+  scheduleDownload(downloadId: string, startAt?: string): Promise<void>
+  listDownloadCache(): Promise<CacheEntry[]>
+  pruneDownloadCache(maxAgeDays?: number, maxTotalBytes?: number): Promise<CachePruneReport>
 }
 
 // Utilitaires pour formatter les données
@@ -101,10 +234,10 @@ export const formatDuration = (seconds: number): string => {
 
 export const getEstimatedTimeRemaining = (
   downloaded: number,
-  total: number,
+  total: number | null,
   speed: number,
 ): number => {
-  if (speed === 0 || downloaded >= total) return 0
+  if (total === null || speed === 0 || downloaded >= total) return 0
 
   return (total - downloaded) / speed
 }
@@ -4,9 +4,8 @@ import { join } from '@tauri-apps/api/path'
 import i18n from './i18n'
 import { getGamePaths, GAME_IDS } from './paths'
 import { fetchManifest } from './update-service'
-import { checkFileHash } from './hash-verification'
-import { extractZipAsync } from './zip'
-import { getGameRepository } from './game-data'
+import { archiveExtension, extractArchiveAsync, rollbackInstall } from './zip'
+import { getGameExecutable, getGameRepository } from './game-data'
 import { sendDownloadCompleteNotification } from './notifications'
 import {
   initializeGameDirectoryStructure,
@@ -69,7 +68,8 @@ export async function downloadAndInstallGame(
     // 2. Préparer les chemins
     console.log(`📂 Preparing paths...`)
     const cacheDir = await join(await gamePaths.root, '..', '..', 'cache') // Dossier cache du launcher
-    const zipFileName = `${gameId}-${version}.zip`
+    // L'extension suit l'archive publiée (zip, tar.zst, 7z...)
+    const zipFileName = `${gameId}-${version}${archiveExtension(url)}`
     const zipFilePath = await join(cacheDir, zipFileName)
 
     console.log(`📦 Cache dir: ${cacheDir}`)
@@ -87,9 +87,11 @@ export async function downloadAndInstallGame(
     })
 
     // Démarrer le téléchargement avec le nouveau gestionnaire
+    // Le hash est vérifié pendant le téléchargement : Completed signifie "téléchargé et vérifié"
     const downloadId = await invoke<string>('start_download', {
       url,
       filePath: zipFilePath,
+      expectedSha256: hash,
     })
 
     console.log(`📥 Download started with ID: ${downloadId}`)
@@ -210,10 +212,7 @@ export async function downloadAndInstallGame(
       throw new Error(`File verification failed: ${error}`)
     }
 
-    if (!(await checkFileHash(zipFilePath, hash))) {
-      throw new Error("La vérification d'intégrité a échoué")
-    }
-    console.log(`✅ File integrity verified`)
+    console.log(`✅ File integrity verified during download`)
 
     // 6. Extraire dans le dossier d'installation
     console.log(`📦 Extracting to: ${gamePaths.install}`)
@@ -224,7 +223,8 @@ export async function downloadAndInstallGame(
 
     // Extraction asynchrone avec progression
     await new Promise<void>((resolve, reject) => {
-      extractZipAsync(zipFilePath, gamePaths.install, {
+      extractArchiveAsync(zipFilePath, gamePaths.install, {
+        executable: manifest.executable ?? getGameExecutable(gameId),
         onProgress: (progress) => {
           console.log(
             `📦 Extracting: ${progress.current_file} (${progress.files_processed}/${progress.total_files} - ${progress.percentage.toFixed(1)}%)`,
@@ -242,6 +242,11 @@ export async function downloadAndInstallGame(
           console.log(`✅ Extraction completed`)
           resolve()
         },
+        onReport: (report) => {
+          if (report.rejected.length > 0) {
+            console.warn(`⚠️ ${report.rejected.length} archive entries were rejected:`, report.rejected)
+          }
+        },
         onError: (error) => {
           console.error(`❌ Extraction failed:`, error)
           reject(new Error(`Extraction failed: ${error}`))
@@ -249,9 +254,19 @@ export async function downloadAndInstallGame(
       }).catch(reject)
     })
 
-    // 7. Sauvegarder la version
+    // 7. Sauvegarder la version (l'ancienne accompagne le point de retour de l'installation)
     console.log(`💾 Saving version file...`)
     onProgress?.({ step: 'installing', message: i18n.t('game.install.installing') })
+    try {
+      const previousVersion = await invoke<string>('read_text_file', { path: gamePaths.versionFile })
+
+      await invoke('write_text_file', {
+        path: await previousVersionFile(gamePaths.config),
+        content: previousVersion,
+      })
+    } catch {
+      // Première installation : pas de version précédente
+    }
     await invoke('write_text_file', {
       path: gamePaths.versionFile,
       content: version,
@@ -359,6 +374,42 @@ export async function updateGame(
   }
 }
 
+async function previousVersionFile(configDir: string): Promise<string> {
+  return await join(configDir, 'version.previous.txt')
+}
+
+/**
+ * Revient à la version installée avant la dernière mise à jour
+ * (par exemple si la nouvelle version ne se lance pas)
+ */
+export async function rollbackGame(gameId: string): Promise<GameInstallResult> {
+  try {
+    const gamePaths = await getGamePaths(gameId)
+    const previousVersionPath = await previousVersionFile(gamePaths.config)
+    const previousVersion = await invoke<string>('read_text_file', { path: previousVersionPath })
+
+    await rollbackInstall(gamePaths.install)
+    await invoke('write_text_file', { path: gamePaths.versionFile, content: previousVersion })
+    await invoke('delete_file', { path: previousVersionPath })
+
+    console.log(`⏪ Game ${gameId} rolled back to ${previousVersion}`)
+
+    return {
+      success: true,
+      version: previousVersion,
+    }
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error)
+
+    console.error('Game rollback failed:', errorMessage)
+
+    return {
+      success: false,
+      error: errorMessage,
+    }
+  }
+}
+
 /**
  * Fonction helper pour installer Lysandra spécifiquement
  */
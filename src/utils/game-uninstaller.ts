@@ -1,6 +1,7 @@
 import { invoke } from '@tauri-apps/api/core'
 
 import { getGamePaths, GAME_IDS } from './paths'
+import { discardInstallRollback } from './zip'
 
 export type GameUninstallResult = {
   success: boolean
@@ -38,7 +39,16 @@ export async function uninstallGame(gameId: string): Promise<GameUninstallResult
 
     const gamePaths = await getGamePaths(gameId)
 
-    // 1. Supprimer le dossier d'installation (contient les binaires du jeu)
+    // 1. Supprimer d'abord la version précédente conservée pour un retour arrière :
+    // restée seule, elle reprendrait la place de l'installation supprimée
+    try {
+      await discardInstallRollback(gamePaths.install)
+    } catch (error) {
+      console.error(`❌ Could not delete previous install: ${error}`)
+      throw new Error('Impossible de supprimer la version précédente du jeu')
+    }
+
+    // Supprimer le dossier d'installation (contient les binaires du jeu)
     try {
       await emitUninstallEvent(gameId, 'removing-install', 'Suppression des fichiers du jeu...')
       await invoke('delete_directory', { path: gamePaths.install })
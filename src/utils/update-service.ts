@@ -35,6 +35,8 @@ export async function fetchManifest(owner: string, repo: string) {
       version: manifest.version,
       url: platformZip.url,
       hash: platformZip.sha256,
+      // Chemin relatif de l'exécutable, à rendre exécutable après extraction (optionnel)
+      executable: platformZip.executable as string | undefined,
     }
   } catch (error) {
     console.error(`❌ Failed to fetch manifest:`, error)
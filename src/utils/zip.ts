@@ -11,22 +11,73 @@ export type ExtractionProgress = {
   status: 'starting' | 'extracting' | 'completed' | 'failed'
 }
 
+export type RejectionReason =
+  | 'InvalidName'
+  | 'AbsolutePath'
+  | 'ParentTraversal'
+  | 'SymlinkOutsideTarget'
+  | 'SymlinkUnsupported'
+  | 'ThroughSymlink'
+  | 'UnsupportedEntryType'
+
+// Entrée de l'archive ignorée parce qu'elle sortirait du dossier d'installation
+// ou que son type (lien physique, périphérique...) n'est pas pris en charge
+export type RejectedEntry = {
+  name: string
+  reason: RejectionReason
+}
+
+export type ExtractionReport = {
+  files_extracted: number
+  symlinks_created: number
+  rejected: RejectedEntry[]
+}
+
+// Limites contre les archives piégées ; un champ absent garde sa valeur par défaut
+export type ExtractionLimits = {
+  max_total_bytes?: number // défaut : 64 Go
+  max_entries?: number // défaut : 100 000
+  max_compression_ratio?: number // par entrée, ou pour toute l'archive en tar et 7z ; défaut : 2000
+  max_path_length?: number // en octets, défaut : 1024
+}
+
 export type ExtractionOptions = {
+  limits?: ExtractionLimits
+  // Chemin relatif du binaire à rendre exécutable, quels que soient les droits stockés dans l'archive
+  executable?: string
   onProgress?: (progress: ExtractionProgress) => void
   onComplete?: () => void
   onError?: (error: string) => void
+  onReport?: (report: ExtractionReport) => void
+}
+
+// Extensions des formats reconnus ; le format réel est détecté au contenu de l'archive
+const ARCHIVE_EXTENSIONS = ['.tar.gz', '.tgz', '.tar.xz', '.tar.zst', '.tar', '.7z', '.zip']
+
+// Extension à donner au fichier téléchargé depuis `url` (`.zip` par défaut)
+export function archiveExtension(url: string): string {
+  const path = url.split(/[?#]/)[0].toLowerCase()
+
+  return ARCHIVE_EXTENSIONS.find((extension) => path.endsWith(extension)) ?? '.zip'
 }
 
-// Version synchrone simple
-export async function extractZip(filePath: string, extractTo: string): Promise<void> {
-  return await invoke('extract_zip_file', {
+// Version synchrone simple : zip, tar (gz, xz, zst) ou 7z
+export async function extractArchive(
+  filePath: string,
+  extractTo: string,
+  limits?: ExtractionLimits,
+  executable?: string,
+): Promise<ExtractionReport> {
+  return await invoke<ExtractionReport>('extract_archive', {
     filePath,
     extractTo,
+    limits,
+    executable,
   })
 }
 
 // Version asynchrone avec événements de progression
-export async function extractZipAsync(
+export async function extractArchiveAsync(
   filePath: string,
   extractTo: string,
   options?: ExtractionOptions,
@@ -58,12 +109,16 @@ export async function extractZipAsync(
     }
 
     // Démarrer l'extraction asynchrone
-    await invoke('extract_zip_file_async', {
+    const report = await invoke<ExtractionReport>('extract_archive_async', {
       filePath,
       extractTo,
       extractionId,
+      limits: options?.limits,
+      executable: options?.executable,
     })
 
+    options?.onReport?.(report)
+
     return extractionId
   } catch (error) {
     unlisten?.()
@@ -73,3 +128,23 @@ export async function extractZipAsync(
     throw error
   }
 }
+
+/** @deprecated Utiliser `extractArchive`, qui accepte tous les formats */
+export const extractZip = extractArchive
+
+/** @deprecated Utiliser `extractArchiveAsync`, qui accepte tous les formats */
+export const extractZipAsync = extractArchiveAsync
+
+// L'extraction prépare la nouvelle version à côté de l'installation puis l'échange avec
+// l'ancienne, conservée comme point de retour (`<install>.previous`)
+export async function rollbackInstall(installDir: string): Promise<void> {
+  return await invoke('rollback_install', { installDir })
+}
+
+export async function hasInstallRollback(installDir: string): Promise<boolean> {
+  return await invoke<boolean>('has_install_rollback', { installDir })
+}
+
+export async function discardInstallRollback(installDir: string): Promise<void> {
+  return await invoke('discard_install_rollback', { installDir })
+}